@@ -55,6 +55,7 @@ pub enum SpriteId {
     Corner,
     Edge,
     Window,
+    Rock,
     Floor(FloorType),
     Circuitry(CircuitryType),
     Door(DoorStatus),
@@ -65,7 +66,8 @@ pub enum SpriteId {
     Decoration(DecorationType),
     MapSector,
     MapStation,
-    MapShip
+    MapShip,
+    MapUnknown
 }
 
 #[derive(Hash, PartialEq, Eq, Clone)]
@@ -110,6 +112,44 @@ pub fn draw_input_state(state: &str, ctx: &mut Context) -> GameResult<()> {
     Ok(())
 }
 
+pub fn draw_hud(text: &str, ctx: &mut Context) -> GameResult<()> {
+    let font = graphics::Font::new(ctx, "/04B_03.TTF", 12).unwrap();
+    let hud_graphics = graphics::Text::new(ctx, text, &font).unwrap();
+    let hud_y = get_screen_coordinates(ctx).h - 40.0;
+    graphics::set_color(ctx, graphics::BLACK)?;
+    let hud_box = graphics::Rect::new(20.0, hud_y, hud_graphics.width() as f32 + 20.0, 20.0);
+    graphics::rectangle(ctx, graphics::DrawMode::Fill, hud_box)?;
+    graphics::set_color(ctx, graphics::WHITE)?;
+    graphics::draw(ctx, &hud_graphics, graphics::Point2::new(30.0, hud_y), 0.0)?;
+
+    Ok(())
+}
+
+// Tab moves a whole stack, with shift held it splits off a single item
+pub fn stack_amount(keymod: Mod) -> usize {
+    if keymod == LSHIFTMOD {
+        1
+    } else {
+        usize::max_value()
+    }
+}
+
+pub fn draw_bubble(ctx: &mut Context, text: &str, pos: Position, camera: Position) -> GameResult<()> {
+    let font = graphics::Font::new(ctx, "/04B_03.TTF", 12).unwrap();
+    let bubble_graphics = graphics::Text::new(ctx, text, &font).unwrap();
+    let tile = get_tile_params(ctx, pos, camera, None).dest;
+    // centered over the tile, one line above it
+    let x = tile.x + (GRID_SIZE as f32 - bubble_graphics.width() as f32) / 2.0;
+    let y = tile.y - 24.0;
+    graphics::set_color(ctx, graphics::BLACK)?;
+    let bubble_box = graphics::Rect::new(x - 6.0, y, bubble_graphics.width() as f32 + 12.0, 20.0);
+    graphics::rectangle(ctx, graphics::DrawMode::Fill, bubble_box)?;
+    graphics::set_color(ctx, graphics::WHITE)?;
+    graphics::draw(ctx, &bubble_graphics, graphics::Point2::new(x, y), 0.0)?;
+
+    Ok(())
+}
+
 pub fn draw_selection_with_parameters<T: Clone + ToString>(selection: &SelectionStorage<T>, ctx: &mut Context, position: Position, orientation: TextAlign, cursor: bool, draw_empty: bool) -> GameResult<()> {
     let font = graphics::Font::new(ctx, "/04B_03.TTF", 12).unwrap();
     let mut inventory_item_position = 0.0;
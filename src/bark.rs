@@ -0,0 +1,49 @@
+use rand::{thread_rng, Rng};
+
+use misc::Position;
+use world::WorldData;
+use constants::{BARK_DISTANCE, BARK_CHANCE, BARK_DURATION};
+
+// a line shown over the tile of the npc saying it until the universe time runs past it
+pub struct Bubble {
+    pub speaker: String,
+    pub position: Position,
+    pub text: String,
+    pub until: u64
+}
+
+pub fn update_barks(data: &mut WorldData) {
+    let now = data.universe.time.as_secs();
+    data.bubbles.retain(|bubble| bubble.until > now);
+
+    let mut rng = thread_rng();
+    let mut spoken = vec![];
+    for (index, npc) in data.level.npc.iter().enumerate() {
+        if let Some(ref npc) = *npc {
+            let pos = Position::from_int(index as i32);
+            // bubbles move along with their speaker
+            if let Some(bubble) = data.bubbles.iter_mut().find(|bubble| bubble.speaker == npc.name) {
+                bubble.position = pos;
+                continue;
+            }
+            // nobody barks at someone they are talking to
+            if pos == data.level.player.front_tile || pos.dist(&data.level.player.position) > BARK_DISTANCE || !rng.gen_bool(BARK_CHANCE) {
+                continue;
+            }
+
+            let barks: Vec<_> = npc.barks.iter()
+                .filter(|bark| bark.conditions.iter().all(|condition| condition.holds_for(data, Some(npc))))
+                .collect();
+            if let Some(bark) = rng.choose(&barks) {
+                spoken.push(Bubble {
+                    speaker: npc.name.clone(),
+                    position: pos,
+                    text: bark.text.clone(),
+                    until: now + BARK_DURATION
+                });
+            }
+        }
+    }
+
+    data.bubbles.extend(spoken);
+}
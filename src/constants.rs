@@ -2,4 +2,36 @@ pub const LEVEL_SIZE: i32 = 40;
 pub const TILE_SIZE: i32 = 8;
 pub const PIXEL_SCALE: i32 = 4;
 pub const GRID_SIZE: i32 = TILE_SIZE * PIXEL_SCALE;
-pub const MOVEMENT_DURATION: u64 = 275;
\ No newline at end of file
+pub const MOVEMENT_DURATION: u64 = 275;
+pub const SENSOR_RANGE: f32 = 4.0;
+pub const TRAVEL_DURATION: u64 = 1000;
+pub const FUEL_CAPACITY: i32 = 20;
+pub const LAYOVER_TICKS: u32 = 8;
+pub const TIME_SCALE: u32 = 60;
+pub const JUMP_TIME: u64 = 3600;
+pub const SECONDS_PER_DAY: u64 = 86400;
+pub const START_TIME: u64 = 28800;
+pub const STARTING_CREDITS: i32 = 50;
+pub const NPC_STEP_DURATION: u64 = 600;
+pub const WANDER_CHANCE: f64 = 0.3;
+pub const VISITOR_WANDER_RADIUS: i32 = 2;
+pub const LOG_LINE_LENGTH: usize = 40;
+pub const TRADE_REPUTATION: i32 = 1;
+pub const GIFT_REPUTATION: i32 = 2;
+// percent of the price per point of standing, standing counts up to its limit either way
+pub const STANDING_DISCOUNT: i32 = 2;
+pub const STANDING_LIMIT: i32 = 10;
+pub const DISTRUST_STANDING: i32 = -5;
+pub const RESTOCK_INTERVAL: u64 = 14400;
+pub const GUARD_SIGHT: i32 = 4;
+pub const THEFT_REPUTATION: i32 = 5;
+pub const RECRUIT_LIKING: i32 = 3;
+pub const ENGINEER_FUEL_SAVING: f64 = 0.5;
+pub const NAVIGATOR_JUMP_TIME: u64 = 1800;
+pub const BARK_DISTANCE: f32 = 4.0;
+pub const BARK_CHANCE: f64 = 0.05;
+// in universe seconds, a few seconds of play
+pub const BARK_DURATION: u64 = 240;
+pub const CIRCUITRY_CONDUCTORS: usize = 1;
+// percent a trader adds to what it asks and takes off what it pays
+pub const TRADE_MARGIN: i32 = 10;
\ No newline at end of file
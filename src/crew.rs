@@ -0,0 +1,63 @@
+use objects::{Location, Routine, CrewStation};
+use world::WorldData;
+use constants::VISITOR_WANDER_RADIUS;
+
+pub fn aboard(data: &WorldData) -> bool {
+    match data.level.location {
+        Location::Ship(_) => true,
+        _ => false
+    }
+}
+
+// the ship the player is on learns which of its stations are manned
+pub fn update_crew(data: &mut WorldData) {
+    if let Location::Ship(ref id) = data.level.location {
+        let stations = data.level.manned_stations();
+        if let Some(ship) = data.universe.ship_mut(id) {
+            ship.crew = stations;
+        }
+    }
+}
+
+pub fn recruit(data: &mut WorldData) {
+    let name = match data.level.current_npc() {
+        Some(npc) => {
+            npc.routine = Routine::Follow;
+            npc.ship = None;
+            npc.name.clone()
+        },
+        None => return
+    };
+    data.level.player.write_log(&name, "Joined the crew");
+}
+
+pub fn follow(data: &mut WorldData) {
+    if let Some(npc) = data.level.current_npc() {
+        npc.routine = Routine::Follow;
+    }
+    update_crew(data);
+}
+
+pub fn assign(data: &mut WorldData, station: CrewStation) {
+    let position = match data.level.station_position(station) {
+        Some(position) if aboard(data) => position,
+        _ => return
+    };
+    if let Some(npc) = data.level.current_npc() {
+        npc.routine = Routine::Manning(station, position);
+    }
+    update_crew(data);
+}
+
+pub fn dismiss(data: &mut WorldData) {
+    let front_tile = data.level.player.front_tile;
+    let name = match data.level.current_npc() {
+        Some(npc) => {
+            npc.routine = Routine::Wander(front_tile, VISITOR_WANDER_RADIUS);
+            npc.name.clone()
+        },
+        None => return
+    };
+    update_crew(data);
+    data.level.player.write_log(&name, "Left the crew");
+}
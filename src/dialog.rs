@@ -1,10 +1,24 @@
 use feature::map::MapFeature;
+use objects::{Item, DoorStatus, CrewStation, Npc};
+use misc::Position;
+use quest::{QuestEvent, notify, start_quest, complete_quest, quest_active, quest_finished};
+use storage::{Node, SelectionStorage};
+use world::WorldData;
+use crew;
+use constants::GIFT_REPUTATION;
+
+pub const START_NODE: &str = "start";
+// scripts can greet a returning player or a distrusted one differently
+pub const AGAIN_NODE: &str = "again";
+pub const DISTRUST_NODE: &str = "distrust";
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct DialogItem {
     pub text: String,
     pub response: String,
-    pub action: Option<DialogAction>
+    pub action: Option<DialogAction>,
+    pub conditions: Vec<Condition>,
+    pub effects: Vec<Effect>
 }
 
 impl ToString for DialogItem {
@@ -16,5 +30,327 @@ impl ToString for DialogItem {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum DialogAction {
     Trade,
-    Map(MapFeature)
-}
\ No newline at end of file
+    Map(MapFeature),
+    Dock,
+    Undock,
+    Land,
+    Scan,
+    Status,
+    Intercomm
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Condition {
+    HasItem(Item),
+    MissingItem(Item),
+    Flag(String),
+    NotFlag(String),
+    Reputation(String, i32),
+    Credits(i32),
+    QuestActive(String),
+    QuestFinished(String),
+    Chosen(String),
+    NotChosen(String),
+    Liked(i32),
+    Crew,
+    NotCrew,
+    Aboard,
+    Unpowered
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Effect {
+    GiveItem(Item),
+    TakeItem(Item),
+    SetFlag(String),
+    ClearFlag(String),
+    OpenDoor(Position),
+    UnlockDoor(Position),
+    StartQuest(String),
+    CompleteQuest(String),
+    Credits(i32),
+    Reputation(String, i32),
+    Like(i32),
+    Recruit,
+    Follow,
+    Assign(CrewStation),
+    Dismiss
+}
+
+impl Condition {
+    // conversations are about the npc the player faces
+    pub fn holds(&self, data: &WorldData) -> bool {
+        self.holds_for(data, data.level.npc.get(data.level.player.front_tile))
+    }
+
+    // conditions about an npc's memory, liking or crew membership are judged for the given npc
+    pub fn holds_for(&self, data: &WorldData, npc: Option<&Npc>) -> bool {
+        match *self {
+            Condition::HasItem(ref item) => data.level.player.has(item.clone()),
+            Condition::MissingItem(ref item) => !data.level.player.has(item.clone()),
+            Condition::Flag(ref flag) => data.universe.has_flag(flag),
+            Condition::NotFlag(ref flag) => !data.universe.has_flag(flag),
+            Condition::Reputation(ref faction, minimum) => data.universe.reputation(faction) >= minimum,
+            Condition::Credits(minimum) => data.level.player.credits >= minimum,
+            Condition::QuestActive(ref id) => quest_active(data, id),
+            Condition::QuestFinished(ref id) => quest_finished(data, id),
+            Condition::Chosen(ref node) => npc.map_or(false, |npc| npc.memory.has_chosen(node)),
+            Condition::NotChosen(ref node) => !npc.map_or(false, |npc| npc.memory.has_chosen(node)),
+            Condition::Liked(minimum) => npc.map_or(false, |npc| data.universe.standing(npc) >= minimum),
+            Condition::Crew => npc.map_or(false, |npc| npc.routine.is_crew()),
+            Condition::NotCrew => !npc.map_or(false, |npc| npc.routine.is_crew()),
+            Condition::Aboard => crew::aboard(data),
+            Condition::Unpowered => data.level.circuitry.iter().any(|circuitry| circuitry.as_ref().map_or(false, |circuitry| !circuitry.powered()))
+        }
+    }
+}
+
+impl Effect {
+    pub fn apply(&self, data: &mut WorldData) {
+        match *self {
+            Effect::GiveItem(ref item) => {
+                data.level.player.inventory.add(item.clone(), 1);
+            },
+            Effect::TakeItem(ref item) => {
+                if data.level.player.remove(item.clone()) {
+                    // handing an item to someone in a conversation is a gift
+                    if let Some(npc) = data.level.current_npc() {
+                        npc.inventory.add(item.clone(), 1);
+                        npc.memory.gifts += 1;
+                        npc.memory.reputation += GIFT_REPUTATION;
+                    }
+                    notify(data, QuestEvent::Delivered(item.clone()));
+                }
+            },
+            Effect::SetFlag(ref flag) => {
+                data.universe.set_flag(flag);
+            },
+            Effect::ClearFlag(ref flag) => {
+                data.universe.clear_flag(flag);
+            },
+            Effect::OpenDoor(position) => {
+                if let Some(door) = data.level.doors.get_mut(position) {
+                    door.status = DoorStatus::Open;
+                }
+            },
+            Effect::UnlockDoor(position) => {
+                if let Some(door) = data.level.doors.get_mut(position) {
+                    if door.status == DoorStatus::Locked {
+                        door.status = DoorStatus::Closed;
+                    }
+                }
+            },
+            Effect::StartQuest(ref id) => {
+                start_quest(data, id);
+            },
+            Effect::CompleteQuest(ref id) => {
+                complete_quest(data, id);
+            },
+            Effect::Credits(amount) => {
+                data.level.player.credits += amount;
+            },
+            Effect::Reputation(ref faction, amount) => {
+                data.universe.change_reputation(faction, amount);
+            },
+            Effect::Like(amount) => {
+                if let Some(npc) = data.level.current_npc() {
+                    npc.memory.reputation += amount;
+                }
+            },
+            Effect::Recruit => {
+                crew::recruit(data);
+            },
+            Effect::Follow => {
+                crew::follow(data);
+            },
+            Effect::Assign(station) => {
+                crew::assign(data, station);
+            },
+            Effect::Dismiss => {
+                crew::dismiss(data);
+            }
+        }
+    }
+}
+
+// a short line an npc says on its own when the player walks by
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Bark {
+    pub text: String,
+    pub conditions: Vec<Condition>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Transition {
+    Goto(String),
+    Back,
+    End
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialogOption {
+    pub text: String,
+    pub transition: Transition,
+    pub conditions: Vec<Condition>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialogNode {
+    pub id: String,
+    pub response: String,
+    pub action: Option<DialogAction>,
+    pub effects: Vec<Effect>,
+    pub options: Vec<DialogOption>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialogGraph {
+    pub nodes: Vec<DialogNode>
+}
+
+impl DialogGraph {
+    pub fn node(&self, id: &str) -> Option<&DialogNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    // dialogs stored with npcs and terminals are trees, every node gets its path as id
+    pub fn from_tree(tree: &Node<DialogItem>) -> DialogGraph {
+        let mut graph = DialogGraph {
+            nodes: vec![]
+        };
+        graph.insert_tree(tree, START_NODE.to_string());
+
+        graph
+    }
+
+    fn insert_tree(&mut self, tree: &Node<DialogItem>, id: String) {
+        let mut options = vec![];
+        for (index, child) in tree.children.iter().enumerate() {
+            let child_id = format!("{}.{}", id, index);
+            options.push(DialogOption {
+                text: child.value.text.clone(),
+                transition: Transition::Goto(child_id.clone()),
+                conditions: child.value.conditions.clone()
+            });
+            self.insert_tree(child, child_id);
+        }
+
+        self.nodes.push(DialogNode {
+            id,
+            response: tree.value.response.clone(),
+            action: tree.value.action.clone(),
+            effects: tree.value.effects.clone(),
+            options
+        });
+    }
+}
+
+pub enum DialogStep {
+    Show(Option<DialogAction>),
+    End
+}
+
+// walks a dialog graph while a conversation is open, the visited nodes are kept to go back
+pub struct Conversation {
+    graph: DialogGraph,
+    current: String,
+    history: Vec<String>,
+    options: Vec<DialogOption>,
+    pub view: Node<DialogItem>
+}
+
+impl Conversation {
+    pub fn new(graph: DialogGraph, start: &str, data: &mut WorldData) -> Conversation {
+        let start = if graph.node(start).is_some() { start } else { START_NODE };
+        let mut conversation = Conversation {
+            graph,
+            current: start.to_string(),
+            history: vec![],
+            options: vec![],
+            view: Node::new()
+        };
+        conversation.enter(start, data);
+
+        conversation
+    }
+
+    pub fn prev(&mut self) {
+        self.view.children.prev();
+    }
+
+    pub fn next(&mut self) {
+        self.view.children.next();
+    }
+
+    pub fn choose(&mut self, data: &mut WorldData) -> DialogStep {
+        let transition = match self.options.get(self.view.children.current_index()) {
+            Some(option) => option.transition.clone(),
+            None => Transition::End
+        };
+
+        match transition {
+            Transition::Goto(id) => {
+                // only what the player picked counts as chosen, not the node a conversation opens with
+                if let Some(npc) = data.level.current_npc() {
+                    npc.memory.remember(&id);
+                }
+                self.history.push(self.current.clone());
+                DialogStep::Show(self.enter(&id, data))
+            },
+            Transition::Back => self.back(data),
+            Transition::End => DialogStep::End
+        }
+    }
+
+    // going back shows the previous node again without repeating its effects
+    pub fn back(&mut self, data: &mut WorldData) -> DialogStep {
+        match self.history.pop() {
+            Some(id) => {
+                self.current = id;
+                self.refresh(data);
+                DialogStep::Show(None)
+            },
+            None => DialogStep::End
+        }
+    }
+
+    fn enter(&mut self, id: &str, data: &mut WorldData) -> Option<DialogAction> {
+        self.current = id.to_string();
+        let action = match self.graph.node(id) {
+            Some(node) => {
+                for effect in node.effects.iter() {
+                    effect.apply(data);
+                }
+                node.action.clone()
+            },
+            None => None
+        };
+        self.refresh(data);
+
+        action
+    }
+
+    // options whose conditions fail are left out
+    fn refresh(&mut self, data: &WorldData) {
+        self.options.clear();
+        self.view = Node::new();
+        if let Some(node) = self.graph.node(&self.current) {
+            self.view.value.response = node.response.clone();
+            for option in node.options.iter() {
+                if option.conditions.iter().all(|condition| condition.holds(data)) {
+                    self.options.push(option.clone());
+                    self.view.children.insert(Node {
+                        value: DialogItem {
+                            text: option.text.clone(),
+                            response: "".to_string(),
+                            action: None,
+                            conditions: option.conditions.clone(),
+                            effects: vec![]
+                        },
+                        children: SelectionStorage::new()
+                    });
+                }
+            }
+        }
+    }
+}
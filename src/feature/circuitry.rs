@@ -6,6 +6,8 @@ use app::{draw_selection_with_parameters, draw_tile};
 use game::{InputState, GameState};
 use misc::{Position, TextAlign};
 use objects::Item;
+use quest::{QuestEvent, notify};
+use constants::CIRCUITRY_CONDUCTORS;
 
 #[derive(PartialEq, Eq)]
 enum Mode {
@@ -72,23 +74,32 @@ impl GameState for Handler {
                 match self.mode {
                     Mode::Inventory => {
                         let mut current = None;
-                        if let Some(item) = data.level.player.inventory.current() {
-                            current = Some(item.clone());
+                        if let Some(stack) = data.level.player.inventory.current() {
+                            current = Some(stack.item);
                         }
                         if let Some(item) = current {
                             if item == Item::PowerConductor {
-                                let item = data.level.player.inventory.extract_current();
-                                if item.is_some() {
-                                    &data.level.current_circuitry().unwrap().parts.insert(item.unwrap());
+                                // only as many conductors as the tile still needs go in
+                                let fitted = data.level.current_circuitry().unwrap().parts.count(&Item::PowerConductor);
+                                let needed = CIRCUITRY_CONDUCTORS.saturating_sub(fitted).max(1);
+                                let stack = data.level.player.inventory.take_current(needed);
+                                if stack.is_some() {
+                                    let powered = data.level.powered_circuitry();
+                                    &data.level.current_circuitry().unwrap().parts.add_stack(stack.unwrap());
                                     data.level.update_power();
+                                    if data.level.powered_circuitry() > powered {
+                                        let location = data.level.location.to_string();
+                                        data.level.player.write_log("Power restored", &format!("Repaired the circuitry on {}", location));
+                                    }
+                                    notify(data, QuestEvent::Powered);
                                 }
                             }
                         }
                     },
                     Mode::Circuitry => {
-                        let item = data.level.current_circuitry().unwrap().parts.extract_current();
-                        if let Some(item) = item {    
-                            data.level.player.inventory.insert(item.clone());
+                        let stack = data.level.current_circuitry().unwrap().parts.take_current(1);
+                        if let Some(stack) = stack {    
+                            data.level.player.inventory.add_stack(stack);
                             data.level.update_power();
                         }
                     }
@@ -9,7 +9,7 @@ use misc::Direction;
 use objects::*;
 use storage::{SelectionStorage, Node};
 use misc::Position;
-use constants::GRID_SIZE;
+use constants::{GRID_SIZE, CIRCUITRY_CONDUCTORS};
 
 pub struct Handler {
     edit_cursor: Position,
@@ -60,6 +60,10 @@ impl Handler {
             selection_storage.insert("Decoration".to_string());
         }
 
+        if let Some(_) = data.level.pilot_seats.get(self.edit_cursor) {
+            selection_storage.insert("Pilot Seat".to_string());
+        }
+
         if self.edit_cursor.x == data.level.player.position.x && self.edit_cursor.y == data.level.player.position.y {
             selection_storage.insert("Player".to_string());
         }
@@ -113,6 +117,7 @@ impl GameState for Handler {
                 data.level.storages.remove(self.edit_cursor);
                 data.level.floor.remove(self.edit_cursor);
                 data.level.decorations.remove(self.edit_cursor);
+                data.level.pilot_seats.remove(self.edit_cursor);
                 data.level.update_power();
             },
             Keycode::W => {
@@ -120,7 +125,7 @@ impl GameState for Handler {
             },
             Keycode::C => {
                 let mut parts = SelectionStorage::new();
-                parts.insert(Item::PowerConductor);
+                parts.add(Item::PowerConductor, CIRCUITRY_CONDUCTORS);
                 data.level.circuitry.insert(self.edit_cursor, Circuitry {parts, variant: CircuitryType::Inactive});
                 data.level.update_power();
             },
@@ -129,7 +134,7 @@ impl GameState for Handler {
                 data.level.update_power();
             },
             Keycode::S => {
-                data.level.storages.insert(self.edit_cursor, Storage { content: SelectionStorage::new(), face: Direction::Down });
+                data.level.storages.insert(self.edit_cursor, Storage { content: SelectionStorage::new(), face: Direction::Down, owner: None });
             },
             Keycode::X => {
                 data.level.decorations.insert(self.edit_cursor, Decoration { variant: DecorationType::Display, face: Direction::Down });
@@ -137,8 +142,11 @@ impl GameState for Handler {
             Keycode::D => {
                 data.level.doors.insert(self.edit_cursor, Door { status: DoorStatus::Closed, variant: DoorType::Passage, face: Direction::Down});
             },
+            Keycode::P => {
+                data.level.pilot_seats.insert(self.edit_cursor, PilotSeat { front: Direction::Down });
+            },
             Keycode::T => {
-                data.level.terminals.insert(self.edit_cursor, Terminal { variant: TerminalType::Intercomm, dialog: Node::new(), front: Direction::Down});
+                data.level.terminals.insert(self.edit_cursor, Terminal { variant: TerminalType::Intercomm, dialog: Node::new(), script: None, front: Direction::Down});
             },
             Keycode::Tab => {
                 if let Some(ref mut deco) = data.level.decorations.get_mut(self.edit_cursor) {
@@ -184,6 +192,22 @@ impl GameState for Handler {
                         },
                     }
                 }
+                if let Some(ref mut pilot_seat) = data.level.pilot_seats.get_mut(self.edit_cursor) {
+                    match pilot_seat.front {
+                        Direction::Up => {
+                            pilot_seat.front = Direction::Right;
+                        },
+                        Direction::Right => {
+                            pilot_seat.front = Direction::Down;
+                        },
+                        Direction::Down => {
+                            pilot_seat.front = Direction::Left;
+                        },
+                        Direction::Left => {
+                            pilot_seat.front = Direction::Up;
+                        },
+                    }
+                }
                 if let Some(ref mut storage) = data.level.storages.get_mut(self.edit_cursor) {
                     match storage.face {
                         Direction::Up => {
@@ -213,6 +237,9 @@ impl GameState for Handler {
                                 wall.variant = WallType::Window;
                             },
                             WallType::Window => {
+                                wall.variant = WallType::Rock;
+                            },
+                            WallType::Rock => {
                                 wall.variant = WallType::Corner;
                             }
                         }
@@ -240,6 +267,9 @@ impl GameState for Handler {
                                 door.status = DoorStatus::Closed;
                             },
                             DoorStatus::Closed => {
+                                door.status = DoorStatus::Locked;
+                            },
+                            DoorStatus::Locked => {
                                 door.status = DoorStatus::Open;
                             }
                         }
@@ -0,0 +1,190 @@
+use ggez::{Context, GameResult};
+use ggez::event::{Keycode, Mod};
+
+use app::{draw_input_state, draw_dialog};
+use game::{InputState, GameState};
+use world::WorldData;
+use dialog::{DialogAction, DialogItem};
+use storage::{Node, SelectionStorage};
+use objects::Location;
+use feature::map::MapFeature;
+use feature::terminal::{dock, undock, land, scan};
+use constants::FUEL_CAPACITY;
+
+pub struct Handler {
+    change_state: Option<InputState>,
+    dialog: Node<DialogItem>
+}
+
+impl Handler {
+    pub fn new(data: &mut WorldData) -> Handler {
+        data.level.sit_down();
+    	Handler {
+            change_state: None,
+            dialog: helm_dialog()
+        }
+    }
+}
+
+impl GameState for Handler {
+
+    fn world_running(&self) -> bool {
+        true
+    }
+
+    fn change_state(&mut self, _ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
+        match self.change_state {
+            Some(InputState::World) => {
+                self.change_state = None;
+                if data.level.stand_up() {
+                    Some(Box::new(super::world::Handler::new()))
+                } else {
+                    self.dialog.value.response = "Someone is in the way, can not get up".to_string();
+                    None
+                }
+            },
+            Some(InputState::Map(feature)) => {
+                self.change_state = None;
+                Some(Box::new(super::map::Handler::new(feature, data)))
+            },
+            _ => None,
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+        match keycode {
+            Keycode::Escape => {
+                self.change_state = Some(InputState::World);
+            },
+            Keycode::Return => {
+                if self.dialog.children.iter().len() > 0 {
+                    let mut action = None;
+                    if let Some(dialog_item) = self.dialog.children.current() {
+                        action = dialog_item.value.action.clone();
+                    }
+                    let mut response = None;
+                    if let Some(action) = action {
+                        match action {
+                            DialogAction::Map(feature) => {
+                                self.change_state = Some(InputState::Map(feature));
+                            },
+                            DialogAction::Dock => {
+                                response = Some(dock(data));
+                            },
+                            DialogAction::Undock => {
+                                response = Some(undock(data));
+                            },
+                            DialogAction::Land => {
+                                response = Some(land(data));
+                            },
+                            DialogAction::Scan => {
+                                response = Some(scan(data));
+                            },
+                            DialogAction::Status => {
+                                response = Some(status(data));
+                            },
+                            DialogAction::Intercomm => {
+                                response = Some(intercomm(data));
+                            },
+                            _ => { }
+                        }
+                    }
+                    self.dialog = self.dialog.children.current().unwrap().clone();
+                    if let Some(response) = response {
+                        self.dialog.value.response = response;
+                    }
+                } else {
+                    // the helm stays open until the pilot leaves the seat
+                    self.dialog = helm_dialog();
+                }
+            },
+            Keycode::Up => {
+                self.dialog.children.prev();
+            },
+            Keycode::Down => {
+                self.dialog.children.next();
+            },
+            _ => ()
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
+        draw_input_state(&format!("Helm | {}", data.universe.calendar()), ctx)?;
+
+        draw_dialog(&self.dialog, ctx)
+    }
+}
+
+fn status(data: &mut WorldData) -> String {
+    if let Location::Ship(ref ship_id) = data.level.location {
+        if let Some(ship) = data.universe.ship(ship_id) {
+            let whereabouts = if ship.is_docked() {
+                format!("docked at {}", ship.docked.to_string())
+            } else {
+                format!("in space at {}", ship.position.to_string())
+            };
+
+            return format!("{} {} | fuel {}/{} | route {} jumps", ship.id, whereabouts, ship.fuel, FUEL_CAPACITY, ship.route.len());
+        }
+    }
+
+    "No ship systems".to_string()
+}
+
+// hails whatever the ship is docked at, or the first contact sharing its position
+fn intercomm(data: &mut WorldData) -> String {
+    if let Location::Ship(ref ship_id) = data.level.location {
+        if let Some(ship) = data.universe.ship(ship_id) {
+            if ship.is_docked() {
+                return data.universe.hail(&ship.docked);
+            }
+
+            let contact = data.universe.ships.iter()
+                .find(|other| other.position == ship.position && other.id != ship.id)
+                .map(|other| Location::Ship(other.id.clone()));
+            if let Some(contact) = contact {
+                return data.universe.hail(&contact);
+            }
+            if let Some(station) = data.universe.station_at(&ship.position) {
+                return data.universe.hail(&Location::Station(station.id.clone()));
+            }
+        }
+    }
+
+    data.universe.hail(&Location::Space)
+}
+
+fn helm_dialog() -> Node<DialogItem> {
+    let mut children = SelectionStorage::new();
+    for &(text, ref action) in [
+        ("Navigate", DialogAction::Map(MapFeature::Navigate)),
+        ("Scan", DialogAction::Scan),
+        ("Dock", DialogAction::Dock),
+        ("Land", DialogAction::Land),
+        ("Undock", DialogAction::Undock),
+        ("Status", DialogAction::Status),
+        ("Intercomm", DialogAction::Intercomm)
+    ].iter() {
+        children.insert(Node {
+            value: DialogItem {
+                text: text.to_string(),
+                response: "".to_string(),
+                action: Some(action.clone()),
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        });
+    }
+
+    Node {
+        value: DialogItem {
+            text: "".to_string(),
+            response: "helm".to_string(),
+            action: None,
+            conditions: vec![],
+            effects: vec![]
+        },
+        children
+    }
+}
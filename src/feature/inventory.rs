@@ -1,10 +1,10 @@
 use ggez::{Context, GameResult};
 use ggez::event::{Keycode, Mod};
 
-use app::{draw_selection_with_parameters, draw_input_state};
+use app::{draw_selection_with_parameters, draw_input_state, stack_amount};
 use game::{InputState, GameState};
 use world::WorldData;
-use objects::{Receipe, Item};
+use objects::{Receipe, Item, Stack};
 use misc::{Position, TextAlign};
 use storage::SelectionStorage;
 use feature::map::MapFeature;
@@ -16,7 +16,7 @@ enum Mode {
 }
 
 pub struct Handler {
-    craft_area: SelectionStorage<Item>,
+    craft_area: SelectionStorage<Stack>,
     change_state: Option<InputState>,
     mode: Mode
 }
@@ -31,8 +31,8 @@ impl Handler {
     }
 
     pub fn reset_craft_area(&mut self, data: &mut WorldData) {
-        while let Some(item) = self.craft_area.extract_current() {
-            data.level.player.inventory.insert(item);
+        while let Some(stack) = self.craft_area.extract_current() {
+            data.level.player.inventory.add_stack(stack);
         }
     }
 }
@@ -53,7 +53,7 @@ impl GameState for Handler {
         }
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, keymod: Mod, _repeat: bool) {
         match keycode {
             Keycode::Escape => {
                 self.reset_craft_area(data);
@@ -90,15 +90,15 @@ impl GameState for Handler {
             Keycode::Tab => {
                 match self.mode {
                     Mode::Inventory => {
-                        let item = data.level.player.inventory.extract_current();
-                        if item.is_some() {
-                            self.craft_area.insert(item.unwrap());
+                        let stack = data.level.player.inventory.take_current(stack_amount(keymod));
+                        if stack.is_some() {
+                            self.craft_area.add_stack(stack.unwrap());
                         }
                     },
                     Mode::Crafting => {
-                        let item = self.craft_area.extract_current();
-                        if item.is_some() {
-                            data.level.player.inventory.insert(item.unwrap());
+                        let stack = self.craft_area.take_current(stack_amount(keymod));
+                        if stack.is_some() {
+                            data.level.player.inventory.add_stack(stack.unwrap());
                         }
                     }
                 }
@@ -106,7 +106,7 @@ impl GameState for Handler {
             Keycode::Return => {
                 match self.mode {
                     Mode::Inventory => {
-                        match data.level.player.inventory.current() {
+                        match data.level.player.inventory.current().map(|stack| stack.item) {
                             Some(Item::Navcomp) => {
                                 self.change_state = Some(InputState::Map(MapFeature::View));
                             },
@@ -114,11 +114,13 @@ impl GameState for Handler {
                         }
                     },
                     Mode::Crafting => {
-                        let ref crafts = &self.craft_area.storage();
-                        let products = Receipe::receipe_match(crafts, &data.receipes);
-                        if let Some(receipe) = products.get(0) {
-                            self.craft_area.clear();
-                            data.level.player.inventory.insert(receipe.result.clone());
+                        let result = Receipe::receipe_match(&self.craft_area, &data.receipes).get(0).map(|receipe| (*receipe).clone());
+                        if let Some(receipe) = result {
+                            // only what the receipe needs is used up, the rest stays in the craft area
+                            for incredient in receipe.incredients.iter() {
+                                self.craft_area.take(&incredient.item, incredient.count);
+                            }
+                            data.level.player.inventory.add(receipe.result, 1);
                         }
                     },
                 }
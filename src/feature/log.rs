@@ -0,0 +1,65 @@
+use ggez::{Context, GameResult};
+use ggez::event::{Keycode, Mod};
+
+use world::WorldData;
+use app::{draw_input_state, draw_selection_with_parameters};
+use game::{InputState, GameState};
+use storage::SelectionStorage;
+use misc::{Position, TextAlign, wrap_text};
+use constants::LOG_LINE_LENGTH;
+
+pub struct Handler {
+    change_state: Option<InputState>
+}
+
+impl Handler {
+    pub fn new() -> Handler {
+    	Handler {
+            change_state: None
+        }
+    }
+}
+
+impl GameState for Handler {
+
+    fn change_state(&mut self, _ctx: &mut Context, _data: &mut WorldData) -> Option<Box<GameState>> {
+        match self.change_state {
+            Some(InputState::World) => {
+                self.change_state = None;
+                Some(Box::new(super::world::Handler::new()))
+            },
+            _ => None,
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+        match keycode {
+            Keycode::Escape | Keycode::L => {
+                self.change_state = Some(InputState::World);
+            },
+            Keycode::Up => {
+                data.level.player.log.prev();
+            },
+            Keycode::Down => {
+                data.level.player.log.next();
+            },
+            _ => ()
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
+        draw_input_state(&format!("Log | {} entries", data.level.player.log.iter().len()), ctx)?;
+
+        draw_selection_with_parameters(&data.level.player.log, ctx, Position { x: 360, y: 80 }, TextAlign::Left, true, true)?;
+
+        if let Some(entry) = data.level.player.log.current() {
+            let mut message = SelectionStorage::new();
+            for line in wrap_text(&entry.message, LOG_LINE_LENGTH) {
+                message.insert(line);
+            }
+            draw_selection_with_parameters(&message, ctx, Position { x: 400, y: 80 }, TextAlign::Right, false, false)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,14 +1,18 @@
 use ggez::{Context, event::*, GameResult};
+use rand;
 
 use storage::SelectionStorage;
 use savegame;
 use game::GameState;
 use world::WorldData;
-use app::{draw_selection, BackdropId, draw_backdrop};
+use app::{draw_selection, draw_input_state, BackdropId, draw_backdrop};
 
 pub struct Handler {
 	saves: SelectionStorage<SaveType>,
-    loading: Option<SaveType>
+    loading: Option<SaveType>,
+    // a new game generates its universe unless the one in levels/universe.txt is picked
+    from_file: bool,
+    seed: String
 }
 
 #[derive(Clone, Debug)]
@@ -31,7 +35,9 @@ impl Handler {
         data.overlay = true;
     	let mut menu = Handler {
     		saves: SelectionStorage::new(),
-            loading: None
+            loading: None,
+            from_file: false,
+            seed: String::new()
     	};
 
         menu.saves.insert(SaveType::Continue);
@@ -39,20 +45,42 @@ impl Handler {
 
     	menu
     }
+
+    fn starting_new(&mut self) -> bool {
+        match self.saves.current() {
+            Some(&SaveType::New) => true,
+            _ => false
+        }
+    }
+
+    fn generating(&mut self) -> bool {
+        self.starting_new() && !self.from_file
+    }
 }
 
 impl GameState for Handler {
-    fn change_state(&mut self, _ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
+    fn change_state(&mut self, ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
         let mut state: Option<Box<GameState>> = None;
         if let Some(ref savegame) = self.loading {
             match savegame {
                 SaveType::New => {
-                    savegame::static_levels::static_ship_tech(data);
+                    data.levels.clear();
+                    data.universe = if self.from_file {
+                        savegame::universe_file::load_universe()
+                    } else {
+                        // an empty seed rolls a fresh universe, a typed one reproduces it
+                        let seed = self.seed.parse().unwrap_or_else(|_| rand::random());
+                        savegame::generated_universe::generated_universe(seed)
+                    };
+                    data.load_backdrops(ctx);
+                    let ship_id = data.universe.player_ship.clone();
+                    savegame::static_levels::static_ship_tech(data, &ship_id);
                     data.overlay = false;
                     state = Some(Box::new(super::world::Handler::new()));
                 },
                 SaveType::Continue => {
                     savegame::load_game(data);
+                    data.load_backdrops(ctx);
                     data.overlay = false;
                     state = Some(Box::new(super::world::Handler::new()));
                 }
@@ -71,9 +99,19 @@ impl GameState for Handler {
 	        Keycode::Down => {
 	            self.saves.next();
 	        },
+            Keycode::Left | Keycode::Right => {
+                if self.starting_new() {
+                    self.from_file = !self.from_file;
+                }
+            },
             Keycode::Return => {
                 self.loading = Some(self.saves.current().unwrap().clone())
             },
+            Keycode::Backspace => {
+                if self.generating() {
+                    self.seed.pop();
+                }
+            },
             Keycode::Escape => {
                 ctx.quit().expect("game should have quit");
             },
@@ -81,9 +119,31 @@ impl GameState for Handler {
 	    }
     }
 
+    fn text_input_event(&mut self, _ctx: &mut Context, _world_data: &mut WorldData, text: String) {
+        if self.generating() {
+            // digits that would overflow the seed are not taken
+            for c in text.chars().filter(|c| c.is_ascii_digit()) {
+                let seed = format!("{}{}", self.seed, c);
+                if seed.parse::<u64>().is_ok() {
+                    self.seed = seed;
+                }
+            }
+        }
+    }
+
     fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
         draw_backdrop(ctx, &data.backdrops, &BackdropId::MapSector)?;
 
+        if self.generating() {
+            if self.seed.is_empty() {
+                draw_input_state("Generated universe | seed: random", ctx)?;
+            } else {
+                draw_input_state(&format!("Generated universe | seed: {}", self.seed), ctx)?;
+            }
+        } else if self.starting_new() {
+            draw_input_state("Universe from levels/universe.txt", ctx)?;
+        }
+
         draw_selection(&self.saves, ctx, true, false)
     }
 }
\ No newline at end of file
@@ -5,10 +5,11 @@ use ggez::event::{Keycode, Mod};
 use world::WorldData;
 use misc::{Position, Direction};
 use game::{InputState, GameState};
-use constants::GRID_SIZE;
+use constants::{GRID_SIZE, TRAVEL_DURATION};
 use app::*;
-use storage::SelectionStorage;
+use storage::{SelectionStorage, Node};
 use objects::Location;
+use dialog::DialogItem;
 
 #[derive(PartialEq, Eq)]
 pub enum Mode {
@@ -22,38 +23,42 @@ pub enum MapFeature {
     Navigate
 }
 
+#[derive(Clone)]
+pub struct Contact {
+    location: Location,
+    distance: f32,
+    docking: Option<String>,
+    faction: Option<String>
+}
+
+impl ToString for Contact {
+    fn to_string(&self) -> String {
+        let mut details = vec![self.location.to_string(), format!("{:.1}", self.distance)];
+        if let Some(ref docking) = self.docking {
+            details.push(docking.clone());
+        }
+        if let Some(ref faction) = self.faction {
+            details.push(faction.clone());
+        }
+
+        details.join(" | ")
+    }
+}
+
 pub struct Handler {
     cursor: Position,
     change_state: Option<InputState>,
     map_info_selection: SelectionStorage<String>,
-    map_selection: SelectionStorage<Location>,
+    map_selection: SelectionStorage<Contact>,
     feature: MapFeature,
-    mode: Mode
+    mode: Mode,
+    dialog: Option<Node<DialogItem>>
 }
 
 impl Handler {
     pub fn new(feature: MapFeature, data: &mut WorldData) -> Handler {
         data.overlay = true;
-        let mut cursor = Position {x: 0, y: 0};
-
-        match data.universe.player_location {
-            Location::Ship(ref ship_id) => {
-                for ship in data.universe.ships.iter_mut() {
-                    if &ship.id == ship_id {
-                        cursor = ship.position;
-                    }
-                }
-            },
-            Location::Station(ref station_id) => {
-                for station in data.universe.stations.iter_mut() {
-                    if &station.id == station_id {
-                        cursor = station.position;
-                    }
-                }
-            },
-            Location::Space => { },
-            Location::Planet(_) => { }
-        }
+        let cursor = data.universe.player_position().unwrap_or(Position {x: 0, y: 0});
 
     	let mut handler = Handler {
             cursor,
@@ -61,7 +66,8 @@ impl Handler {
             map_selection: SelectionStorage::new(),
             map_info_selection: SelectionStorage::new(),
             feature,
-            mode: Mode::Universe
+            mode: Mode::Universe,
+            dialog: None
         };
 
         let map_selection = handler.get_map_selection(data);
@@ -72,6 +78,89 @@ impl Handler {
         handler
     }
 
+    // plans from the last queued waypoint to the cursor and appends the path to the ship's route
+    fn queue_route(&mut self, data: &mut WorldData, target: Option<Location>) {
+        if let Location::Ship(ref ship_id) = data.universe.player_location.clone() {
+            let mut planned = None;
+            if let Some(ship) = data.universe.ship(ship_id) {
+                let from = *ship.route.last().unwrap_or(&ship.position);
+                let fuel = ship.fuel - ship.route.len() as i32;
+                planned = Some(data.universe.plan_route(from, self.cursor, fuel));
+            }
+
+            match planned {
+                Some(Some(path)) => {
+                    let jumps = path.len();
+                    if let Some(ship) = data.universe.ship_mut(ship_id) {
+                        ship.route.extend(path);
+                        ship.target = target;
+                    }
+                    if jumps == 0 {
+                        if let Some(location) = data.universe.arrive(ship_id) {
+                            data.level.update_exits(&data.universe);
+                            self.show_message(format!("Arrived at {}", location.to_string()));
+                        }
+                    } else {
+                        self.show_message(format!("Route planned, {} jumps", jumps));
+                    }
+                },
+                Some(None) => {
+                    self.show_message("Out of fuel range".to_string());
+                },
+                None => ()
+            }
+        }
+    }
+
+    fn clear_route(&mut self, data: &mut WorldData) {
+        if let Location::Ship(ref ship_id) = data.universe.player_location.clone() {
+            if let Some(ship) = data.universe.ship_mut(ship_id) {
+                ship.route.clear();
+                ship.target = None;
+            }
+        }
+    }
+
+    fn set_target(&mut self, data: &mut WorldData, location: Location) {
+        let target = match location {
+            Location::Station(_) | Location::Planet(_) => Some(location.clone()),
+            _ => None
+        };
+        self.queue_route(data, target);
+    }
+
+    fn navigation_description(&self, data: &WorldData, title: &str) -> String {
+        if let Location::Ship(ref ship_id) = data.universe.player_location {
+            if let Some(ship) = data.universe.ship(ship_id) {
+                if !ship.route.is_empty() {
+                    let eta = ship.route.len() as u64 * TRAVEL_DURATION / 1000;
+                    return format!("{} | route {} jumps | ETA {}s | fuel {}", title, ship.route.len(), eta, ship.fuel);
+                }
+                return format!("{} | fuel {}", title, ship.fuel);
+            }
+        }
+
+        title.to_string()
+    }
+
+    fn hail(&mut self, data: &mut WorldData, location: &Location) {
+        let response = data.universe.hail(location);
+        self.show_message(response);
+    }
+
+    fn show_message(&mut self, response: String) {
+        self.dialog = Some(Node {
+            value: DialogItem {
+                text: "".to_string(),
+                response,
+                action: None,
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        });
+    }
+
     fn update_selections(&mut self, data: &mut WorldData) {
         self.map_info_selection = self.get_map_info_selection(data);
         self.map_selection = self.get_map_selection(data);
@@ -81,8 +170,10 @@ impl Handler {
         let mut selection_storage: SelectionStorage<String> = SelectionStorage::new();
         selection_storage.insert(self.cursor.to_string());
         selection_storage.insert("-".to_string());
+        let discovered = data.universe.is_discovered(&self.cursor);
+        let in_sensor_range = data.universe.in_sensor_range(&self.cursor);
         for sector in data.universe.sectors.iter() {
-            if sector.position == self.cursor {
+            if sector.position == self.cursor && discovered {
                 selection_storage.insert(sector.id.clone());
                 selection_storage.insert("-".to_string());
             }
@@ -90,7 +181,11 @@ impl Handler {
         let mut player_present = false;
         for station in data.universe.stations.iter() {
             if station.position == self.cursor {
-                selection_storage.insert(station.id.clone());
+                if discovered {
+                    selection_storage.insert(station.id.clone());
+                } else if in_sensor_range {
+                    selection_storage.insert("Unknown contact".to_string());
+                }
                 if let Location::Station(ref station_id) = data.universe.player_location {
                     if &station.id == station_id {
                         player_present = true;
@@ -100,12 +195,18 @@ impl Handler {
         }
         for ship in data.universe.ships.iter() {
             if ship.position == self.cursor {
-                selection_storage.insert(ship.id.clone());
+                let mut player_ship = false;
                 if let Location::Ship(ref ship_id) = data.universe.player_location {
                     if &ship.id == ship_id {
                         player_present = true;
+                        player_ship = true;
                     }
                 }
+                if discovered || player_ship {
+                    selection_storage.insert(ship.id.clone());
+                } else if in_sensor_range {
+                    selection_storage.insert("Unknown contact".to_string());
+                }
             }
         }
 
@@ -117,17 +218,49 @@ impl Handler {
         selection_storage
     }
 
-    fn get_map_selection(&mut self, data: &mut WorldData) -> SelectionStorage<Location> {
-        let mut selection_storage: SelectionStorage<Location> = SelectionStorage::new();
-        selection_storage.insert(Location::Space);
+    fn get_map_selection(&mut self, data: &mut WorldData) -> SelectionStorage<Contact> {
+        let mut selection_storage: SelectionStorage<Contact> = SelectionStorage::new();
+        let distance = match data.universe.player_position() {
+            Some(player_position) => player_position.dist(&self.cursor),
+            None => 0.0
+        };
+        selection_storage.insert(Contact {
+            location: Location::Space,
+            distance,
+            docking: None,
+            faction: None
+        });
+        if !data.universe.is_discovered(&self.cursor) {
+            return selection_storage;
+        }
         for station in data.universe.stations.iter() {
             if station.position == self.cursor {
-                selection_storage.insert(Location::Station(station.id.clone()));
+                selection_storage.insert(Contact {
+                    location: Location::Station(station.id.clone()),
+                    distance,
+                    docking: Some("dock".to_string()),
+                    faction: Some(station.faction.clone())
+                });
+            }
+        }
+        for planet in data.universe.planets.iter() {
+            if planet.position == self.cursor {
+                selection_storage.insert(Contact {
+                    location: Location::Planet(planet.id.clone()),
+                    distance,
+                    docking: Some("land".to_string()),
+                    faction: None
+                });
             }
         }
         for ship in data.universe.ships.iter() {
             if ship.position == self.cursor {
-                selection_storage.insert(Location::Ship(ship.id.clone()));
+                selection_storage.insert(Contact {
+                    location: Location::Ship(ship.id.clone()),
+                    distance,
+                    docking: None,
+                    faction: Some(ship.faction.clone())
+                });
             }
         }
 
@@ -142,13 +275,27 @@ impl GameState for Handler {
             Some(InputState::World) => {
                 self.change_state = None;
                 data.overlay = false;
-                Some(Box::new(super::world::Handler::new()))
+                if data.level.is_seated() {
+                    Some(Box::new(super::helm::Handler::new(data)))
+                } else {
+                    Some(Box::new(super::world::Handler::new()))
+                }
             },
             _ => None,
         }
     }
 
     fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+        if self.dialog.is_some() {
+            match keycode {
+                Keycode::Return | Keycode::Escape => {
+                    self.dialog = None;
+                },
+                _ => ()
+            }
+            return;
+        }
+
         match self.mode {
             Mode::Sector => {
                 match keycode {
@@ -162,15 +309,21 @@ impl GameState for Handler {
                         self.map_selection.next();
                     },
                     Keycode::Return => {
-                        if self.feature == MapFeature::Navigate {
-                            if let Location::Ship(ref ship_id) = data.universe.player_location {
-                                for ship in data.universe.ships.iter_mut() {
-                                    if &ship.id == ship_id {
-                                        ship.position = self.cursor;
-                                    }
-                                }
+                        if let Some(contact) = self.map_selection.current().cloned() {
+                            match self.feature {
+                                MapFeature::Navigate => {
+                                    self.set_target(data, contact.location);
+                                    self.update_selections(data);
+                                },
+                                MapFeature::View => {
+                                    self.hail(data, &contact.location);
+                                },
                             }
-                            self.update_selections(data);
+                        }
+                    },
+                    Keycode::H => {
+                        if let Some(contact) = self.map_selection.current().cloned() {
+                            self.hail(data, &contact.location);
                         }
                     },
                     _ => ()
@@ -202,31 +355,31 @@ impl GameState for Handler {
                             MapFeature::Navigate => {
                                 let mut enter_sector = false;
                                 for ref sector in data.universe.sectors.iter() {
-                                    if sector.position == self.cursor {
+                                    if sector.position == self.cursor && data.universe.is_discovered(&sector.position) {
                                         self.mode = Mode::Sector;
                                         enter_sector = true;
                                     }
                                 }
                                 if self.feature == MapFeature::Navigate && !enter_sector {
-                                    if let Location::Ship(ref ship_id) = data.universe.player_location {
-                                        for ship in data.universe.ships.iter_mut() {
-                                            if &ship.id == ship_id {
-                                                ship.position = self.cursor;
-                                            }
-                                        }
-                                    }
+                                    self.queue_route(data, None);
                                     self.update_selections(data);
                                 }
                             },
                             MapFeature::View => {
                                 for ref sector in data.universe.sectors.iter() {
-                                    if sector.position == self.cursor {
+                                    if sector.position == self.cursor && data.universe.is_discovered(&sector.position) {
                                         self.mode = Mode::Sector;
                                     }
                                 }
                             },
                         }
-                    }
+                    },
+                    Keycode::Backspace => {
+                        if self.feature == MapFeature::Navigate {
+                            self.clear_route(data);
+                            self.show_message("Route cleared".to_string());
+                        }
+                    },
                     _ => ()
                 }
             },
@@ -251,7 +404,7 @@ impl GameState for Handler {
                 }
                 match self.feature {
                     MapFeature::Navigate => {
-                        let sector_description = format!("Navigation Sector {}", self.cursor.to_string());
+                        let sector_description = self.navigation_description(data, &format!("Navigation Sector {}", self.cursor.to_string()));
                         draw_input_state(&sector_description, ctx)?
                     },
                     MapFeature::View => {
@@ -259,15 +412,16 @@ impl GameState for Handler {
                         draw_input_state(&sector_description, ctx)?
                     },
                 }
-                draw_selection(&self.map_selection, ctx, false, false)?;
+                draw_selection(&self.map_selection, ctx, true, false)?;
             },
             Mode::Universe => {
                 match self.feature {
                     MapFeature::Navigate => {
-                        draw_input_state("Navigation Universe", ctx)?;
+                        let description = self.navigation_description(data, &format!("Navigation Universe | seed {}", data.universe.seed));
+                        draw_input_state(&description, ctx)?;
                     },
                     MapFeature::View => {
-                        draw_input_state("Map Universe", ctx)?;
+                        draw_input_state(&format!("Map Universe | seed {}", data.universe.seed), ctx)?;
                     },
                 }
 
@@ -275,25 +429,56 @@ impl GameState for Handler {
                 draw_selection(&self.map_info_selection, ctx, false, false)?;
 
                 for sector in data.universe.sectors.iter() {
-                    let p = get_tile_params(ctx, sector.position, data.camera, None);
-                    add_sprite(&mut data.sprites, &SpriteId::MapSector, p);
+                    if data.universe.is_discovered(&sector.position) {
+                        let p = get_tile_params(ctx, sector.position, data.camera, None);
+                        add_sprite(&mut data.sprites, &SpriteId::MapSector, p);
+                    }
                 }
                 for station in data.universe.stations.iter() {
                     let p = get_tile_params(ctx, station.position, data.camera, None);
-                    add_sprite(&mut data.sprites, &SpriteId::MapStation, p);
+                    if data.universe.is_discovered(&station.position) {
+                        add_sprite(&mut data.sprites, &SpriteId::MapStation, p);
+                    } else if data.universe.in_sensor_range(&station.position) {
+                        add_sprite(&mut data.sprites, &SpriteId::MapUnknown, p);
+                    }
                 }
                 for ship in data.universe.ships.iter() {
                     let p = get_tile_params(ctx, ship.position, data.camera, None);
-                    add_sprite(&mut data.sprites, &SpriteId::MapShip, p);
+                    let player_ship = data.universe.player_location == Location::Ship(ship.id.clone());
+                    if player_ship || data.universe.is_discovered(&ship.position) {
+                        add_sprite(&mut data.sprites, &SpriteId::MapShip, p);
+                    } else if data.universe.in_sensor_range(&ship.position) {
+                        add_sprite(&mut data.sprites, &SpriteId::MapUnknown, p);
+                    }
                 }
                 draw_spritebatch(ctx, &mut data.sprites, &SpriteId::MapSector)?;
                 draw_spritebatch(ctx, &mut data.sprites, &SpriteId::MapShip)?;
                 draw_spritebatch(ctx, &mut data.sprites, &SpriteId::MapStation)?;
+                draw_spritebatch(ctx, &mut data.sprites, &SpriteId::MapUnknown)?;
 
-                graphics::set_color(ctx, graphics::Color{r: 0.2, g: 0.8, b: 0.2, a: 1.0,})?;
-                let viewport_pos = self.cursor.viewport(data.camera);
                 let sceen_horizontal_center = get_screen_coordinates(ctx).w / 2.0 - (GRID_SIZE / 2) as f32;
                 let sceen_vertical_center = get_screen_coordinates(ctx).h / 2.0 - (GRID_SIZE / 2) as f32;
+
+                let mut route = vec![];
+                if let Location::Ship(ref ship_id) = data.universe.player_location {
+                    if let Some(ship) = data.universe.ship(ship_id) {
+                        route = ship.route.clone();
+                    }
+                }
+                graphics::set_color(ctx, graphics::Color{r: 0.67, g: 0.86, b: 0.0, a: 1.0,})?;
+                for waypoint in route {
+                    let viewport_pos = waypoint.viewport(data.camera);
+                    let marker = graphics::Rect::new(
+                        viewport_pos.x as f32 + sceen_horizontal_center + (GRID_SIZE / 2 - 2) as f32,
+                        viewport_pos.y as f32 + sceen_vertical_center + (GRID_SIZE / 2 - 2) as f32,
+                        4.0,
+                        4.0
+                    );
+                    graphics::rectangle(ctx, graphics::DrawMode::Fill, marker)?;
+                }
+
+                graphics::set_color(ctx, graphics::Color{r: 0.2, g: 0.8, b: 0.2, a: 1.0,})?;
+                let viewport_pos = self.cursor.viewport(data.camera);
                 let cursor = graphics::Rect::new(
                     viewport_pos.x as f32 + sceen_horizontal_center,
                     viewport_pos.y as f32 + sceen_vertical_center,
@@ -304,6 +489,10 @@ impl GameState for Handler {
             }
         }
 
+        if let Some(ref dialog) = self.dialog {
+            draw_dialog(dialog, ctx)?;
+        }
+
         Ok(())
     }
-}
\ No newline at end of file
+}
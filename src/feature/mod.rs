@@ -8,4 +8,7 @@ pub mod mainmenu;
 pub mod npc;
 pub mod npc_trade;
 pub mod storage;
-pub mod map;
\ No newline at end of file
+pub mod map;
+pub mod helm;
+pub mod quests;
+pub mod log;
\ No newline at end of file
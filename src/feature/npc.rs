@@ -1,30 +1,45 @@
-use dialog::DialogAction;
 use ggez::{Context, GameResult};
 use ggez::event::{Keycode, Mod};
 
 use world::WorldData;
 use app::{draw_input_state, draw_dialog};
 use game::{InputState, GameState};
-use storage::Node;
-use dialog::DialogItem;
+use dialog::{DialogAction, DialogStep, Conversation, START_NODE, AGAIN_NODE, DISTRUST_NODE};
+use constants::DISTRUST_STANDING;
+use savegame::dialog_file::resolve_dialog;
+use quest::{QuestEvent, notify};
 
 pub struct Handler {
     change_state: Option<InputState>,
-    dialog: Node<DialogItem>
+    conversation: Conversation
 }
 
 impl Handler {
     pub fn new(data: &mut WorldData) -> Handler {
+        let standing = data.universe.standing(data.level.current_npc().unwrap());
+        let (name, dialog, start) = {
+            let npc = data.level.current_npc().unwrap();
+            let start = if standing <= DISTRUST_STANDING {
+                DISTRUST_NODE
+            } else if npc.memory.visits > 0 {
+                AGAIN_NODE
+            } else {
+                START_NODE
+            };
+            npc.memory.visits += 1;
+            (npc.name.clone(), resolve_dialog(&npc.script, &npc.dialog), start)
+        };
+        notify(data, QuestEvent::Talked(name));
     	Handler {
             change_state: None,
-            dialog: data.level.current_npc().unwrap().dialog.clone()
+            conversation: Conversation::new(dialog, start, data)
         }
     }
 }
 
 impl GameState for Handler {
 
-    fn change_state(&mut self, _ctx: &mut Context, _scene_data: &mut WorldData) -> Option<Box<GameState>> {
+    fn change_state(&mut self, _ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
         match self.change_state {
             Some(InputState::World) => {
                 self.change_state = None;
@@ -32,7 +47,7 @@ impl GameState for Handler {
             },
             Some(InputState::NpcTrade) => {
                 self.change_state = None;
-                Some(Box::new(super::npc_trade::Handler::new()))
+                Some(Box::new(super::npc_trade::Handler::new(data)))
             },
             _ => None,
         }
@@ -47,42 +62,43 @@ impl GameState for Handler {
                 self.change_state = Some(InputState::World);
             },
             Keycode::Return => {
-                if self.dialog.children.iter().len() > 0 {
-                    if let Some(dialog_item) = self.dialog.children.current() {
-                        if let Some(ref action) = dialog_item.value.action {
-                            match *action {
-                                DialogAction::Trade => {
-                                    self.change_state = Some(InputState::NpcTrade);
-                                },
-                                DialogAction::Map(_) => { }
-                            }
+                match self.conversation.choose(data) {
+                    DialogStep::Show(Some(DialogAction::Trade)) => {
+                        self.change_state = Some(InputState::NpcTrade);
+                    },
+                    DialogStep::Show(_) => { },
+                    DialogStep::End => {
+                        if let Some(npc) = data.level.current_npc() {
+                            npc.direction = npc.look_at;
                         }
+                        self.change_state = Some(InputState::World);
                     }
-                    self.dialog = self.dialog.children.current().unwrap().clone();	
-                } else {
-                    if let Some(npc) = data.level.current_npc() {
-                        npc.direction = npc.look_at;
-                    }
-                    self.change_state = Some(InputState::World);
                 }
             },
+            Keycode::Backspace => {
+                self.conversation.back(data);
+            },
             Keycode::Up => {
-                self.dialog.children.prev();
+                self.conversation.prev();
             },
             Keycode::Down => {
-                self.dialog.children.next();
+                self.conversation.next();
             },
             _ => ()
         }
     }
 
     fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
-        {
-            let current_npc = data.level.current_npc().unwrap();
-            draw_input_state(&current_npc.name, ctx)?;
+        match data.level.current_npc() {
+            Some(current_npc) => draw_input_state(&current_npc.name, ctx)?,
+            None => {
+                // the npc left while talking
+                self.change_state = Some(InputState::World);
+                return Ok(());
+            }
         }
 
-        draw_dialog(&self.dialog, ctx)
+        draw_dialog(&self.conversation.view, ctx)
     }
 
 }
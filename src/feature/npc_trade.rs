@@ -2,11 +2,13 @@ use ggez::{Context, GameResult};
 use ggez::event::{Keycode, Mod};
 
 use world::WorldData;
-use app::{draw_input_state, draw_selection_with_parameters};
+use app::{draw_input_state, draw_selection_with_parameters, stack_amount};
 use game::{InputState, GameState};
 use storage::SelectionStorage;
-use objects::Item;
+use objects::{Stack, Npc};
 use misc::{TextAlign, Position};
+use quest::{QuestEvent, notify};
+use constants::{TRADE_MARGIN, TRADE_REPUTATION, STANDING_DISCOUNT, STANDING_LIMIT, DISTRUST_STANDING};
 
 #[derive(PartialEq, Clone)]
 pub enum TradeArea {
@@ -17,33 +19,178 @@ pub enum TradeArea {
 }
 
 pub struct Handler {
-    player_trade_area: SelectionStorage<Item>,
-    npc_trade_area: SelectionStorage<Item>,
+    player_trade_area: SelectionStorage<Stack>,
+    npc_trade_area: SelectionStorage<Stack>,
     active_trade_area: TradeArea,
+    notice: Option<String>,
+    // credits the trader is willing to pay instead of the full balance, accepted with another Return
+    counter_offer: Option<i32>,
+    trader_position: Position,
+    trader_name: String,
     change_state: Option<InputState>
 }
 
 impl Handler {
-    pub fn new() -> Handler {
+    pub fn new(data: &mut WorldData) -> Handler {
+        let trader_position = data.level.player.front_tile;
+        let trader_name = data.level.npc.get(trader_position).map_or("".to_string(), |npc| npc.name.clone());
+
     	Handler {
             player_trade_area: SelectionStorage::new(),
             npc_trade_area: SelectionStorage::new(),
             active_trade_area: TradeArea::PlayerInventory,
+            notice: None,
+            counter_offer: None,
+            trader_position,
+            trader_name,
             change_state: None
         }
     }
 
+    // the trader is looked up by name when it is no longer where the trade started
+    fn find_trader(&self, data: &WorldData) -> Option<Position> {
+        if data.level.npc.get(self.trader_position).map_or(false, |npc| npc.name == self.trader_name) {
+            Some(self.trader_position)
+        } else {
+            data.level.npc.iter().position(|npc| npc.as_ref().map_or(false, |npc| npc.name == self.trader_name))
+                .map(|index| Position::from_int(index as i32))
+        }
+    }
+
+    fn trader<'a>(&self, data: &'a mut WorldData) -> Option<&'a mut Npc> {
+        match self.find_trader(data) {
+            Some(position) => data.level.npc.get_mut(position),
+            None => None
+        }
+    }
+
+    // the player gets their staged items back, the trader's go back to it or leave with it when it is gone
     fn reset_trade_areas(&mut self, data: &mut WorldData) {
-        while let Some(item) = self.npc_trade_area.extract_current() {
-            data.level.current_npc().unwrap().inventory.insert(item);
+        match self.trader(data) {
+            Some(npc) => {
+                while let Some(stack) = self.npc_trade_area.extract_current() {
+                    npc.inventory.add_stack(stack);
+                }
+            },
+            None => {
+                if self.npc_trade_area.iter().next().is_some() {
+                    let description = format!("Left with {} still on the counter", describe(&self.npc_trade_area));
+                    data.level.player.write_log(&self.trader_name, &description);
+                }
+                self.npc_trade_area = SelectionStorage::new();
+            }
+        }
+
+        while let Some(stack) = self.player_trade_area.extract_current() {
+            data.level.player.inventory.add_stack(stack);
+        }
+        self.counter_offer = None;
+    }
+
+    // prices follow the market of the station the trade happens on, elsewhere items go for their base price,
+    // every unit moves the supply before it is priced and traders keep a margin, so selling back never pays,
+    // a trader who likes the player asks less but never less than it pays, one who distrusts them is worse on both
+    fn value(&self, data: &WorldData, items: &SelectionStorage<Stack>, selling: bool) -> i32 {
+        let market = data.universe.market(&data.level.location);
+        let standing = self.find_trader(data)
+            .and_then(|position| data.level.npc.get(position))
+            .map_or(0, |npc| data.universe.standing(npc))
+            .max(-STANDING_LIMIT)
+            .min(STANDING_LIMIT);
+        let percent = if selling {
+            100 - TRADE_MARGIN + standing.min(0) * STANDING_DISCOUNT
+        } else {
+            (100 + TRADE_MARGIN - standing * STANDING_DISCOUNT).max(100 - TRADE_MARGIN + 1)
+        };
+
+        items.iter().map(|stack| {
+            (1..stack.count as i32 + 1).map(|unit| {
+                let supply_change = if selling { unit } else { -unit };
+                let price = market.map_or(stack.item.base_price(), |market| market.price_at(&stack.item, supply_change));
+                (price * percent / 100).max(1)
+            }).sum::<i32>()
+        }).sum()
+    }
+
+    // positive when the player offers more than they ask for
+    fn balance(&self, data: &WorldData) -> i32 {
+        self.value(data, &self.player_trade_area, true) - self.value(data, &self.npc_trade_area, false)
+    }
+
+    fn trade(&mut self, data: &mut WorldData) {
+        let (npc_credits, standing) = match self.trader(data).cloned() {
+            Some(npc) => (npc.credits, data.universe.standing(&npc)),
+            None => return
+        };
+        let bought = self.npc_trade_area.items();
+        let sold = self.player_trade_area.items();
+        if bought.is_empty() && sold.is_empty() {
+            return;
+        }
+        if standing <= DISTRUST_STANDING {
+            self.notice = Some("The trader refuses to deal with you".to_string());
+            return;
         }
 
-        while let Some(item) = self.player_trade_area.extract_current() {
-            data.level.player.inventory.insert(item);
+        let mut balance = self.balance(data);
+        if balance < 0 && data.level.player.credits < -balance {
+            self.notice = Some("Not enough credits".to_string());
+            return;
         }
+        if balance > 0 && npc_credits < balance {
+            // the trader offers what it has left, a second Return takes the offer
+            match self.counter_offer {
+                Some(offer) => balance = offer,
+                None => {
+                    if npc_credits <= 0 {
+                        self.notice = Some("The trader can not pay anything".to_string());
+                    } else {
+                        self.counter_offer = Some(npc_credits);
+                        self.notice = Some(format!("The trader offers {} credits instead, Return accepts", npc_credits));
+                    }
+                    return;
+                }
+            }
+        }
+        if let Some(market) = data.universe.market_mut(&data.level.location) {
+            market.trade(&sold, &bought);
+        }
+
+        let description = format!("Sold {} | bought {}", describe(&self.player_trade_area), describe(&self.npc_trade_area));
+        while let Some(stack) = self.npc_trade_area.extract_current() {
+            data.level.player.inventory.add_stack(stack);
+        }
+
+        if let Some(npc) = self.trader(data) {
+            while let Some(stack) = self.player_trade_area.extract_current() {
+                npc.inventory.add_stack(stack);
+            }
+        }
+        for item in sold.iter() {
+            notify(data, QuestEvent::Delivered(item.clone()));
+        }
+
+        data.level.player.credits += balance;
+        let (name, first_trade) = {
+            let npc = self.trader(data).unwrap();
+            npc.credits -= balance;
+            npc.memory.trades += 1;
+            let returned = npc.memory.take_back(&sold);
+            npc.memory.sold.extend(bought.iter().cloned());
+            if !bought.is_empty() || returned < sold.len() {
+                npc.memory.reputation += TRADE_REPUTATION;
+            }
+            (npc.name.clone(), npc.memory.trades == 1)
+        };
+        if first_trade {
+            data.level.player.write_log(&name, "First trade");
+        }
+        data.level.player.write_log(&name, &format!("{} | {:+} credits", description, balance));
+        self.counter_offer = None;
+        self.notice = None;
     }
 
-    fn draw_trade_area(&self, selection: &SelectionStorage<Item>, ctx: &mut Context, area: TradeArea) -> GameResult<()> {
+    fn draw_trade_area(&self, selection: &SelectionStorage<Stack>, ctx: &mut Context, area: TradeArea) -> GameResult<()> {
         let active = area == self.active_trade_area;
         match area {
             TradeArea::NpcInventory => {
@@ -64,9 +211,22 @@ impl Handler {
     }
 }
 
+fn describe(stacks: &SelectionStorage<Stack>) -> String {
+    if stacks.iter().next().is_none() {
+        return "nothing".to_string();
+    }
+    stacks.iter().map(|stack| stack.to_string()).collect::<Vec<_>>().join(", ")
+}
+
 impl GameState for Handler {
 
-    fn change_state(&mut self, _ctx: &mut Context, _scene_data: &mut WorldData) -> Option<Box<GameState>> {
+    fn change_state(&mut self, _ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
+        if self.change_state.is_none() && self.find_trader(data).is_none() {
+            // the trader left, the player's items go back to the inventory
+            self.reset_trade_areas(data);
+            self.change_state = Some(InputState::World);
+        }
+
         match self.change_state {
             Some(InputState::World) => {
                 self.change_state = None;
@@ -76,49 +236,52 @@ impl GameState for Handler {
         }
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, keymod: Mod, _repeat: bool) {
+        self.notice = None;
 
         match keycode {
             Keycode::Escape => {
                 self.reset_trade_areas(data);
                 self.change_state = Some(InputState::World);
-                if let Some(npc) = data.level.current_npc() {
+                if let Some(npc) = self.trader(data) {
                     npc.direction = npc.look_at;
                 }
             },
-            Keycode::Return => {    
-                while let Some(item) = self.npc_trade_area.extract_current() {
-                    data.level.player.inventory.insert(item);
-                }
-
-                while let Some(item) = self.player_trade_area.extract_current() {
-                    data.level.current_npc().unwrap().inventory.insert(item);
-                }
+            Keycode::Return => {
+                self.trade(data);
             },
             Keycode::Tab => {
+                // a changed trade needs a new offer
+                self.counter_offer = None;
                 match self.active_trade_area {
                     TradeArea::NpcInventory => {
-                        let item = data.level.current_npc().unwrap().inventory.extract_current();
-                        if item.is_some() {
-                            self.npc_trade_area.insert(item.unwrap());
+                        let stack = self.trader(data).and_then(|npc| npc.inventory.take_current(stack_amount(keymod)));
+                        if stack.is_some() {
+                            self.npc_trade_area.add_stack(stack.unwrap());
                         }
                     },
                     TradeArea::NpcStaging => {
-                        let item = self.npc_trade_area.extract_current();
-                        if item.is_some() {
-                            data.level.current_npc().unwrap().inventory.insert(item.unwrap());
+                        if let Some(npc) = self.trader(data) {
+                            if let Some(stack) = self.npc_trade_area.take_current(stack_amount(keymod)) {
+                                npc.inventory.add_stack(stack);
+                            }
                         }
                     },
                     TradeArea::PlayerStaging => {
-                        let item = self.player_trade_area.extract_current();
-                        if item.is_some() {
-                            data.level.player.inventory.insert(item.unwrap());
+                        let stack = self.player_trade_area.take_current(stack_amount(keymod));
+                        if stack.is_some() {
+                            data.level.player.inventory.add_stack(stack.unwrap());
                         }
                     },
                     TradeArea::PlayerInventory => {
-                        let item = data.level.player.inventory.extract_current();
-                        if item.is_some() {
-                            self.player_trade_area.insert(item.unwrap());
+                        let offered = data.level.player.inventory.current().map(|stack| stack.item);
+                        if let Some(item) = offered {
+                            if self.trader(data).map_or(false, |npc| npc.stock.buys(&item)) {
+                                let stack = data.level.player.inventory.take_current(stack_amount(keymod)).unwrap();
+                                self.player_trade_area.add_stack(stack);
+                            } else {
+                                self.notice = Some(format!("The trader does not deal in {:?}", item));
+                            }
                         }
                     },
                 }
@@ -158,7 +321,9 @@ impl GameState for Handler {
             Keycode::Up => {
                 match self.active_trade_area {
                     TradeArea::NpcInventory => {
-                        data.level.current_npc().unwrap().inventory.prev();
+                        if let Some(npc) = self.trader(data) {
+                            npc.inventory.prev();
+                        }
                     },
                     TradeArea::NpcStaging => {
                         self.npc_trade_area.prev();
@@ -174,7 +339,9 @@ impl GameState for Handler {
             Keycode::Down => {
                 match self.active_trade_area {
                     TradeArea::NpcInventory => {
-                        data.level.current_npc().unwrap().inventory.next();
+                        if let Some(npc) = self.trader(data) {
+                            npc.inventory.next();
+                        }
                     },
                     TradeArea::NpcStaging => {
                         self.npc_trade_area.next();
@@ -192,13 +359,27 @@ impl GameState for Handler {
     }
 
     fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
-        draw_input_state("Trade", ctx)?;
-        let npc_inventory = data.level.current_npc().unwrap().inventory.clone();
+        let (npc_credits, buys, npc_inventory) = match self.find_trader(data).and_then(|position| data.level.npc.get(position)) {
+            Some(npc) => (npc.credits, npc.stock.buys_description(), npc.inventory.clone()),
+            None => return Ok(())
+        };
+        let header = match self.notice {
+            Some(ref notice) => format!("Trade | {}", notice),
+            None => format!("Trade | credits {} | trader {} {} | balance {:+}", data.level.player.credits, npc_credits, buys, self.balance(data))
+        };
+        draw_input_state(&header, ctx)?;
         self.draw_trade_area(&npc_inventory, ctx, TradeArea::NpcInventory)?;
         self.draw_trade_area(&self.npc_trade_area, ctx, TradeArea::NpcStaging)?;
         self.draw_trade_area(&self.player_trade_area, ctx, TradeArea::PlayerStaging)?;
         self.draw_trade_area(&data.level.player.inventory, ctx, TradeArea::PlayerInventory)?;
 
+        let mut asked = SelectionStorage::new();
+        asked.insert(format!("asked {}", self.value(data, &self.npc_trade_area, false)));
+        draw_selection_with_parameters(&asked, ctx, Position { x: 200, y: 60 }, TextAlign::Right, false, false)?;
+        let mut offered = SelectionStorage::new();
+        offered.insert(format!("offered {}", self.value(data, &self.player_trade_area, true)));
+        draw_selection_with_parameters(&offered, ctx, Position { x: 520, y: 60 }, TextAlign::Left, false, false)?;
+
         Ok(())
     }
 
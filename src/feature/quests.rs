@@ -0,0 +1,79 @@
+use ggez::{Context, GameResult};
+use ggez::event::{Keycode, Mod};
+
+use world::WorldData;
+use app::{draw_input_state, draw_selection_with_parameters};
+use game::{InputState, GameState};
+use storage::SelectionStorage;
+use misc::{Position, TextAlign};
+use quest::Quest;
+
+pub struct Handler {
+    change_state: Option<InputState>,
+    quests: SelectionStorage<Quest>
+}
+
+impl Handler {
+    pub fn new(data: &mut WorldData) -> Handler {
+        // active quests are listed before the finished ones
+        let mut quests = SelectionStorage::new();
+        for quest in data.level.player.quests.iter().filter(|quest| !quest.finished) {
+            quests.insert(quest.clone());
+        }
+        for quest in data.level.player.quests.iter().filter(|quest| quest.finished) {
+            quests.insert(quest.clone());
+        }
+
+    	Handler {
+            change_state: None,
+            quests
+        }
+    }
+}
+
+impl GameState for Handler {
+
+    fn change_state(&mut self, _ctx: &mut Context, _data: &mut WorldData) -> Option<Box<GameState>> {
+        match self.change_state {
+            Some(InputState::World) => {
+                self.change_state = None;
+                Some(Box::new(super::world::Handler::new()))
+            },
+            _ => None,
+        }
+    }
+
+    fn key_up_event(&mut self, _ctx: &mut Context, _data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+        match keycode {
+            Keycode::Escape | Keycode::Q => {
+                self.change_state = Some(InputState::World);
+            },
+            Keycode::Up => {
+                self.quests.prev();
+            },
+            Keycode::Down => {
+                self.quests.next();
+            },
+            _ => ()
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, _data: &mut WorldData) -> GameResult<()> {
+        let active = self.quests.iter().filter(|quest| !quest.finished).count();
+        draw_input_state(&format!("Quests | {} active", active), ctx)?;
+
+        draw_selection_with_parameters(&self.quests, ctx, Position { x: 360, y: 80 }, TextAlign::Left, true, true)?;
+
+        if let Some(quest) = self.quests.current() {
+            let mut details = SelectionStorage::new();
+            details.insert(quest.description.clone());
+            for task in quest.tasks.iter() {
+                let mark = if task.done { "x" } else { " " };
+                details.insert(format!("[{}] {}", mark, task.objective.to_string()));
+            }
+            draw_selection_with_parameters(&details, ctx, Position { x: 400, y: 80 }, TextAlign::Right, false, false)?;
+        }
+
+        Ok(())
+    }
+}
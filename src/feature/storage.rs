@@ -2,11 +2,12 @@ use ggez::{Context, GameResult};
 use ggez::event::{Keycode, Mod};
 
 use world::WorldData;
-use app::{draw_input_state, draw_selection_with_parameters};
+use app::{draw_input_state, draw_selection_with_parameters, stack_amount};
 use game::{InputState, GameState};
 use misc::{TextAlign, Position};
-use objects::Item;
+use objects::Stack;
 use storage::SelectionStorage;
+use guard;
 
 #[derive(PartialEq, Clone)]
 pub enum StorageArea {
@@ -16,18 +17,20 @@ pub enum StorageArea {
 
 pub struct Handler {
     change_state: Option<InputState>,
-    active_storage_area: StorageArea
+    active_storage_area: StorageArea,
+    notice: Option<String>
 }
 
 impl Handler {
     pub fn new() -> Handler {
     	Handler {
             change_state: None,
-            active_storage_area: StorageArea::Inventory
+            active_storage_area: StorageArea::Inventory,
+            notice: None
         }
     }
 
-    fn draw_storage_area(&self, selection: &SelectionStorage<Item>, ctx: &mut Context, area: StorageArea) -> GameResult<()> {
+    fn draw_storage_area(&self, selection: &SelectionStorage<Stack>, ctx: &mut Context, area: StorageArea) -> GameResult<()> {
         let active = area == self.active_storage_area;
         match area {
             StorageArea::Storage => {
@@ -54,7 +57,9 @@ impl GameState for Handler {
         }
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, keymod: Mod, _repeat: bool) {
+        self.notice = None;
+
         match keycode {
             Keycode::Escape => {
                 self.change_state = Some(InputState::World);
@@ -62,15 +67,16 @@ impl GameState for Handler {
             Keycode::Tab => {
                 match self.active_storage_area {
                     StorageArea::Storage => {
-                        let item = data.level.current_storage().unwrap().content.extract_current();
-                        if item.is_some() {
-                            data.level.player.inventory.insert(item.unwrap());
+                        let stack = data.level.current_storage().unwrap().content.take_current(stack_amount(keymod));
+                        if let Some(stack) = stack {
+                            data.level.player.inventory.add_stack(stack.clone());
+                            self.notice = guard::took_items(data, &stack);
                         }
                     },
                     StorageArea::Inventory => {
-                        let item = data.level.player.inventory.extract_current();
-                        if item.is_some() {
-                            data.level.current_storage().unwrap().content.insert(item.unwrap());
+                        let stack = data.level.player.inventory.take_current(stack_amount(keymod));
+                        if let Some(stack) = stack {
+                            data.level.current_storage().unwrap().content.add_stack(stack);
                         }
                     },
                 }
@@ -125,7 +131,11 @@ impl GameState for Handler {
 
     fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
 
-        draw_input_state("Storage", ctx)?;
+        let header = match self.notice {
+            Some(ref notice) => format!("Storage | {}", notice),
+            None => "Storage".to_string()
+        };
+        draw_input_state(&header, ctx)?;
 
         self.draw_storage_area(&data.level.current_storage().unwrap().content, ctx, StorageArea::Storage)?;
         self.draw_storage_area(&data.level.player.inventory, ctx, StorageArea::Inventory)?;
@@ -3,21 +3,27 @@ use ggez::event::{Keycode, Mod};
 
 use app::{draw_input_state, draw_dialog};
 use game::{InputState, GameState};
-use world::WorldData;
-use dialog::{DialogAction, DialogItem};
-use storage::Node;
+use world::{WorldData, Universe};
+use dialog::{DialogAction, DialogStep, Conversation, START_NODE};
+use objects::Location;
+use misc::Position;
+use savegame::dialog_file::resolve_dialog;
 
 pub struct Handler {
     change_state: Option<InputState>,
-    dialog: Node<DialogItem>
+    conversation: Conversation
 }
 
 impl Handler {
     pub fn new(data: &mut WorldData) -> Handler {
         println!("{:?}", data.level.terminal_connected());
+        let dialog = {
+            let terminal = data.level.current_terminal().unwrap();
+            resolve_dialog(&terminal.script, &terminal.dialog)
+        };
     	Handler {
             change_state: None,
-            dialog: data.level.current_terminal().unwrap().dialog.clone()
+            conversation: Conversation::new(dialog, START_NODE, data)
         }
     }
 }
@@ -38,41 +44,124 @@ impl GameState for Handler {
         }
     }
 
-    fn key_up_event(&mut self, _ctx: &mut Context, _data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+    fn key_up_event(&mut self, _ctx: &mut Context, data: &mut WorldData, keycode: Keycode, _keymod: Mod, _repeat: bool) {
         match keycode {
             Keycode::Escape => {
                 self.change_state = Some(InputState::World);
             },
             Keycode::Return => {
-                if self.dialog.children.iter().len() > 0 {
-                    if let Some(dialog_item) = self.dialog.children.current() {
-                        if let Some(ref action) = dialog_item.value.action {
-                            match *action {
+                match self.conversation.choose(data) {
+                    DialogStep::Show(action) => {
+                        let mut response = None;
+                        if let Some(action) = action {
+                            match action {
                                 DialogAction::Map(feature) => {
                                     self.change_state = Some(InputState::Map(feature));
                                 },
+                                DialogAction::Dock => {
+                                    response = Some(dock(data));
+                                },
+                                DialogAction::Undock => {
+                                    response = Some(undock(data));
+                                },
+                                DialogAction::Land => {
+                                    response = Some(land(data));
+                                },
+                                DialogAction::Scan => {
+                                    response = Some(scan(data));
+                                },
                                 _ => { }
                             }
                         }
+                        if let Some(response) = response {
+                            self.conversation.view.value.response = response;
+                        }
+                    },
+                    DialogStep::End => {
+                        self.change_state = Some(InputState::World);
                     }
-                    self.dialog = self.dialog.children.current().unwrap().clone();	
-                } else {
-                    self.change_state = Some(InputState::World);
                 }
             },
+            Keycode::Backspace => {
+                self.conversation.back(data);
+            },
             Keycode::Up => {
-                self.dialog.children.prev();
+                self.conversation.prev();
             },
             Keycode::Down => {
-                self.dialog.children.next();
+                self.conversation.next();
             },
             _ => ()
         }
     }
 
-    fn draw(&mut self, ctx: &mut Context, _data: &mut WorldData) -> GameResult<()> {
-        draw_input_state("Terminal", ctx)?;
+    fn draw(&mut self, ctx: &mut Context, data: &mut WorldData) -> GameResult<()> {
+        draw_input_state(&format!("Terminal | {}", data.universe.calendar()), ctx)?;
+
+        draw_dialog(&self.conversation.view, ctx)
+    }
+}
+
+pub fn dock(data: &mut WorldData) -> String {
+    if let Location::Ship(ref ship_id) = data.level.location.clone() {
+        let docked = data.universe.ship(ship_id).map(|ship| ship.is_docked()).unwrap_or(false);
+        if docked {
+            return "Undock first".to_string();
+        }
+        if let Some(station) = data.universe.dock(ship_id) {
+            data.level.update_exits(&data.universe);
+            return format!("Docked at {}", station.to_string());
+        }
+        return not_in_range(data, ship_id, "station", |universe, pos| universe.stations_at(pos));
+    }
+
+    "No station in range".to_string()
+}
+
+pub fn land(data: &mut WorldData) -> String {
+    if let Location::Ship(ref ship_id) = data.level.location.clone() {
+        let docked = data.universe.ship(ship_id).map(|ship| ship.is_docked()).unwrap_or(false);
+        if docked {
+            return "Undock first".to_string();
+        }
+        if let Some(planet) = data.universe.land(ship_id) {
+            data.level.update_exits(&data.universe);
+            return format!("Landed on {}", planet.to_string());
+        }
+        return not_in_range(data, ship_id, "planet", |universe, pos| universe.planets_at(pos));
+    }
+
+    "No planet in range".to_string()
+}
 
-        draw_dialog(&self.dialog, ctx)
+// several candidates in the same sector need a target picked on the map first
+fn not_in_range<F>(data: &WorldData, ship_id: &str, kind: &str, candidates: F) -> String
+    where F: Fn(&Universe, &Position) -> Vec<Location> {
+    let here = data.universe.ship(ship_id).map_or(vec![], |ship| candidates(&data.universe, &ship.position));
+    if here.len() > 1 {
+        let names: Vec<String> = here.iter().map(|location| location.to_string()).collect();
+        format!("Several in range: {}, target one on the map", names.join(", "))
+    } else {
+        format!("No {} in range", kind)
     }
-}
\ No newline at end of file
+}
+
+pub fn scan(data: &mut WorldData) -> String {
+    let found = data.universe.scan();
+    if found > 0 {
+        format!("Discovered {} new contacts", found)
+    } else {
+        "Nothing new in sensor range".to_string()
+    }
+}
+
+pub fn undock(data: &mut WorldData) -> String {
+    if let Location::Ship(ref ship_id) = data.level.location.clone() {
+        if data.universe.undock(ship_id) {
+            data.level.update_exits(&data.universe);
+            return "Undocked".to_string();
+        }
+    }
+
+    "Not docked".to_string()
+}
@@ -10,6 +10,7 @@ use objects::*;
 use savegame::*;
 use storage::{Node, SelectionStorage};
 use dialog::DialogItem;
+use guard;
 
 pub struct Handler {
     change_state: Option<InputState>,
@@ -27,8 +28,36 @@ impl Handler {
     fn interact_with_door(&mut self, data: &mut WorldData, powered: bool) {
         let mut location = None;
 
+        let closed = data.level.doors.get(data.level.player.front_tile).map_or(false, |door| door.status == DoorStatus::Closed);
+        if closed {
+            if let Some(refusal) = guard::blocked(data, data.level.player.front_tile) {
+                self.dialog = Some(Node {
+                    value: DialogItem {
+                        text: "".to_string(),
+                        response: refusal,
+                        action: None,
+                        conditions: vec![],
+                        effects: vec![]
+                    },
+                    children: SelectionStorage::new()
+                });
+                return;
+            }
+        }
+
         if let Some(door) = data.level.doors.get_mut(data.level.player.front_tile) {
-            if powered {
+            if door.status == DoorStatus::Locked {
+                self.dialog = Some(Node {
+                    value: DialogItem {
+                        text: "".to_string(),
+                        response: "Locked".to_string(),
+                        action: None,
+                        conditions: vec![],
+                        effects: vec![]
+                    },
+                    children: SelectionStorage::new()
+                });
+            } else if powered {
                 match &door.variant {
                     DoorType::Passage => {
                         match door.status {
@@ -38,6 +67,7 @@ impl Handler {
                             DoorStatus::Open => {
                                 door.status = DoorStatus::Closed;
                             },
+                            DoorStatus::Locked => { }
                         }
                     },
                     DoorType::Exit(new_location) => {
@@ -49,7 +79,9 @@ impl Handler {
                     value: DialogItem {
                         text: "".to_string(),
                         response: "Needs to be powered".to_string(),
-                        action: None
+                        action: None,
+                        conditions: vec![],
+                        effects: vec![]
                     },
                     children: SelectionStorage::new()
                 });
@@ -80,6 +112,16 @@ impl Handler {
         }
     }
 
+    fn interact_with_pilot_seat(&mut self, data: &mut WorldData) {
+        let player_position = data.level.player.position;
+        let seat_position = data.level.player.front_tile;
+        if let Some(ref pilot_seat) = data.level.current_pilot_seat() {
+            if &pilot_seat.front.value() + &seat_position == player_position {
+                self.change_state = Some(InputState::Helm);
+            }
+        }
+    }
+
     fn interact_with_storage(&mut self, data: &mut WorldData) {
         if let Some(_) = data.level.storages.get_mut(data.level.player.front_tile) {
             self.change_state = Some(InputState::Storage);
@@ -98,7 +140,9 @@ impl Handler {
                     value: DialogItem {
                         text: "".to_string(),
                         response: "Needs to be powered".to_string(),
-                        action: None
+                        action: None,
+                        conditions: vec![],
+                        effects: vec![]
                     },
                     children: SelectionStorage::new()
                 });
@@ -109,6 +153,10 @@ impl Handler {
 
 impl GameState for Handler {
 
+    fn world_running(&self) -> bool {
+        true
+    }
+
     fn change_state(&mut self, _ctx: &mut Context, data: &mut WorldData) -> Option<Box<GameState>> {
         match self.change_state {
             Some(InputState::World) => {
@@ -147,6 +195,18 @@ impl GameState for Handler {
                 self.change_state = None;
                 Some(Box::new(super::map::Handler::new(feature, data)))
             },
+            Some(InputState::Helm) => {
+                self.change_state = None;
+                Some(Box::new(super::helm::Handler::new(data)))
+            },
+            Some(InputState::Quests) => {
+                self.change_state = None;
+                Some(Box::new(super::quests::Handler::new(data)))
+            },
+            Some(InputState::Log) => {
+                self.change_state = None;
+                Some(Box::new(super::log::Handler::new()))
+            },
             _ => None,
         }
     }
@@ -221,6 +281,7 @@ impl GameState for Handler {
                             self.interact_with_door(data, powered);
                             self.interact_with_npc(data);
                             self.interact_with_storage(data);
+                            self.interact_with_pilot_seat(data);
                         }
                     },
                 }
@@ -228,6 +289,12 @@ impl GameState for Handler {
             Keycode::I => {
                 self.change_state = Some(InputState::Inventory);
             },
+            Keycode::Q => {
+                self.change_state = Some(InputState::Quests);
+            },
+            Keycode::L => {
+                self.change_state = Some(InputState::Log);
+            },
             Keycode::M => {
                 if data.level.player.has(Item::Navcomp) {
                     self.change_state = Some(InputState::Map(MapFeature::View));
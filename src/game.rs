@@ -11,6 +11,8 @@ use objects::*;
 use misc::*;
 use constants::*;
 use world::WorldData;
+use guard;
+use bark;
 use feature::{*, map::MapFeature};
 
 pub trait GameState {
@@ -27,6 +29,9 @@ pub trait GameState {
     fn text_input_event(&mut self, _ctx: &mut Context, _world: &mut WorldData, _text: String) { }
 
     fn quit_event(&mut self, _ctx: &mut Context, _world: &mut WorldData) -> bool { false }
+
+    // the universe only moves on while the player is out in the world or at the helm, not in menus and overlays
+    fn world_running(&self) -> bool { false }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -41,7 +46,10 @@ pub enum InputState {
     Npc,
     NpcTrade,
     Storage,
-    Map(MapFeature)
+    Map(MapFeature),
+    Helm,
+    Quests,
+    Log
 }
 
 pub struct Handler {
@@ -76,6 +84,38 @@ impl GameState for Handler {
 
         data.level.player.front_tile = &data.level.player.direction.value() + &data.level.player.position;
 
+        if !self.current_ingame_state.world_running() {
+            return Ok(());
+        }
+
+        data.universe.advance_time(get_delta(ctx));
+
+        data.npc_timer += get_delta(ctx);
+
+        if data.npc_timer > Duration::from_millis(NPC_STEP_DURATION) {
+            data.npc_timer = Duration::from_millis(0);
+            data.level.update_npcs(&data.universe);
+            data.level.restock(data.universe.time);
+            guard::enforce_posts(data);
+            bark::update_barks(data);
+        }
+
+        data.travel_timer += get_delta(ctx);
+
+        if data.travel_timer > Duration::from_millis(TRAVEL_DURATION) {
+            data.travel_timer = Duration::from_millis(0);
+            let docked = data.universe.simulate();
+            if data.universe.travel() || docked {
+                data.level.update_exits(&data.universe);
+                data.level.update_visitors(&mut data.universe);
+            }
+            for ship_id in data.universe.strand() {
+                if data.universe.player_location == Location::Ship(ship_id.clone()) {
+                    data.level.player.write_log(&ship_id, "Out of fuel, stranded with the route unfinished");
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -143,12 +183,18 @@ impl GameState for Handler {
                     let p = get_tile_params(ctx, Position::from_int(pos as i32), data.camera, None);
                     match floor.variant {
                         FloorType::Regular => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::Regular), p),
-                        FloorType::Light => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::Light), p)
+                        FloorType::Light => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::Light), p),
+                        FloorType::Soil => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::Soil), p),
+                        FloorType::Gravel => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::Gravel), p),
+                        FloorType::LandingPad => add_sprite(&mut data.sprites, &SpriteId::Floor(FloorType::LandingPad), p)
                     };
                 }
             }
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Floor(FloorType::Regular))?;
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Floor(FloorType::Light))?;
+            draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Floor(FloorType::Soil))?;
+            draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Floor(FloorType::Gravel))?;
+            draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Floor(FloorType::LandingPad))?;
 
             for (pos, item) in data.level.walls.iter().enumerate() {
                 if let Some(wall) = item {
@@ -158,6 +204,7 @@ impl GameState for Handler {
                         WallType::Corner => add_sprite(&mut data.sprites, &SpriteId::Corner, p),
                         WallType::Edge => add_sprite(&mut data.sprites, &SpriteId::Edge, p),
                         WallType::Window => add_sprite(&mut data.sprites, &SpriteId::Window, p),
+                        WallType::Rock => add_sprite(&mut data.sprites, &SpriteId::Rock, p),
                     };
                 }
             }
@@ -165,6 +212,7 @@ impl GameState for Handler {
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Corner)?;
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Edge)?;
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Window)?;
+            draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Rock)?;
 
             for (pos, terminal) in data.level.terminals.iter().enumerate() {
                 if let Some(current_terminal) = terminal {
@@ -199,6 +247,7 @@ impl GameState for Handler {
             }
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Door(DoorStatus::Closed))?;
             draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Door(DoorStatus::Open))?;
+            draw_spritebatch(ctx, &mut data.sprites, &SpriteId::Door(DoorStatus::Locked))?;
 
             for (pos, item) in data.level.generators.iter().enumerate() {
                 if item.is_some() {
@@ -243,6 +292,12 @@ impl GameState for Handler {
             }
 
             draw_tile(ctx, data.level.player.tile(), data.level.player.position.to_int(), data.camera, None)?;
+
+            for bubble in data.bubbles.iter() {
+                draw_bubble(ctx, &bubble.text, bubble.position, data.camera)?;
+            }
+
+            draw_hud(&data.universe.calendar(), ctx)?;
         }
 
         self.current_ingame_state.draw(ctx, data)?;
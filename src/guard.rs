@@ -0,0 +1,97 @@
+use objects::{Stack, NpcType, DoorStatus};
+use misc::Position;
+use world::WorldData;
+use constants::{GUARD_SIGHT, THEFT_REPUTATION};
+
+fn guards(data: &WorldData) -> Vec<Position> {
+    let mut guards = vec![];
+    for (index, npc) in data.level.npc.iter().enumerate() {
+        if let Some(ref npc) = *npc {
+            if npc.variant == NpcType::Guard {
+                guards.push(Position::from_int(index as i32));
+            }
+        }
+    }
+    guards
+}
+
+// the refusal of the guard posted at a door the player has no pass for,
+// a player already behind the door is never kept in
+pub fn blocked(data: &WorldData, door: Position) -> Option<String> {
+    for pos in guards(data) {
+        let npc = data.level.npc.get(pos).unwrap();
+        if let Some(ref post) = npc.post {
+            if post.door == door && !post.is_inside(&data.level.player.position) && !post.passes.iter().any(|pass| pass.holds(data)) {
+                return Some(format!("{}: {}", npc.name, post.refusal));
+            }
+        }
+    }
+    None
+}
+
+// guards shut their doors again when the player got them open without a pass
+pub fn enforce_posts(data: &mut WorldData) {
+    let mut doors = vec![];
+    for pos in guards(data) {
+        if let Some(ref post) = data.level.npc.get(pos).unwrap().post {
+            if post.door != data.level.player.position && blocked(data, post.door).is_some() {
+                doors.push(post.door);
+            }
+        }
+    }
+
+    for door in doors {
+        if let Some(door) = data.level.doors.get_mut(door) {
+            if door.status == DoorStatus::Open {
+                door.status = DoorStatus::Closed;
+            }
+        }
+    }
+}
+
+// the closest guard of the owner that sees the player
+fn witness(data: &WorldData, owner: &str) -> Option<Position> {
+    let player = data.level.player.position;
+    guards(data).into_iter()
+        .filter(|&pos| data.level.npc.get(pos).unwrap().faction == owner)
+        .map(|pos| {
+            let offset = &player - &pos;
+            (pos, offset.x.abs().max(offset.y.abs()))
+        })
+        .filter(|&(_, distance)| distance <= GUARD_SIGHT)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(pos, _)| pos)
+}
+
+// called when the player took items out of the storage in front of them,
+// a guard who sees it takes them back, remembers the theft and locks the door it guards
+pub fn took_items(data: &mut WorldData, stack: &Stack) -> Option<String> {
+    let storage_position = data.level.player.front_tile;
+    let owner = match data.level.storages.get(storage_position).and_then(|storage| storage.owner.clone()) {
+        Some(owner) => owner,
+        None => return None
+    };
+    let guard_position = witness(data, &owner)?;
+
+    if data.level.player.inventory.take(&stack.item, stack.count) {
+        data.level.storages.get_mut(storage_position).unwrap().content.add_stack(stack.clone());
+    }
+    data.universe.change_reputation(&owner, -THEFT_REPUTATION);
+
+    let (name, post) = {
+        let guard = data.level.npc.get_mut(guard_position).unwrap();
+        guard.memory.reputation -= THEFT_REPUTATION;
+        (guard.name.clone(), guard.post.clone())
+    };
+    // a thief caught behind the door is not locked in
+    if let Some(post) = post {
+        if !post.is_inside(&data.level.player.position) {
+            if let Some(door) = data.level.doors.get_mut(post.door) {
+                door.status = DoorStatus::Locked;
+            }
+        }
+    }
+    data.level.player.write_log(&name, &format!("Caught taking {} from {}", stack.to_string(), owner));
+
+    Some(format!("{}: Put that {} back, thief!", name, stack.to_string()))
+}
@@ -7,6 +7,7 @@ extern crate tar;
 extern crate specs;
 #[macro_use]
 extern crate specs_derive;
+extern crate rand;
 
 mod savegame;
 mod game;
@@ -17,6 +18,10 @@ mod misc;
 mod constants;
 mod feature;
 mod dialog;
+mod quest;
+mod guard;
+mod crew;
+mod bark;
 mod app;
 mod world;
 mod components;
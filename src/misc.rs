@@ -125,4 +125,25 @@ impl Direction {
 pub enum TextAlign {
     Left,
     Right
-}
\ No newline at end of file
+}
+
+// breaks a text into lines of at most width characters at the spaces between words
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.len() + 1 + word.len() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
@@ -1,6 +1,6 @@
-use misc::{Direction};
+use misc::{Direction, Position};
 use storage::{SelectionStorage, Node};
-use dialog::DialogItem;
+use dialog::{DialogItem, Condition, Bark};
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub enum Object {
@@ -13,7 +13,8 @@ pub enum WallType {
 	Wall,
 	Corner,
 	Edge,
-	Window
+	Window,
+	Rock
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -37,7 +38,10 @@ pub struct Decoration {
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum FloorType {
 	Regular,
-	Light
+	Light,
+	Soil,
+	Gravel,
+	LandingPad
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -48,7 +52,8 @@ pub struct Floor {
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum DoorStatus {
     Open,
-    Closed
+    Closed,
+    Locked
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Hash)]
@@ -94,6 +99,7 @@ pub enum TerminalType {
 pub struct Terminal {
 	pub variant: TerminalType,
     pub dialog: Node<DialogItem>,
+    pub script: Option<String>,
     pub front: Direction,
 }
 
@@ -110,7 +116,7 @@ pub enum CircuitryType {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Circuitry {
-    pub parts: SelectionStorage<Item>,
+    pub parts: SelectionStorage<Stack>,
     pub variant: CircuitryType
 }
 
@@ -122,8 +128,8 @@ impl Circuitry {
 		}
 	}
 
-	pub fn contains(&self, needle: Item) -> bool {
-		self.parts.iter().find(|&&item| (item == needle)).is_some()
+	pub fn contains(&self, needle: Item, count: usize) -> bool {
+		self.parts.count(&needle) >= count
 	}
 
 	pub fn powered(&self) -> bool {
@@ -139,45 +145,110 @@ pub struct Generator {
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 pub enum Item {
 	PowerConductor,
-	Navcomp
+	Navcomp,
+	Permit
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Receipe {
 	pub result: Item,
-	pub incredients: Vec<Item>
+	pub incredients: Vec<Stack>
 }
 
 impl Receipe {
 	pub fn _receipes_as_incredient<'a>(item: &Item, receipes: &'a Vec<Receipe>) -> Vec<&'a Receipe> {
-		receipes.iter().filter(|receipe| receipe.incredients.contains(item)).collect()
+		receipes.iter().filter(|receipe| receipe.incredients.iter().any(|incredient| &incredient.item == item)).collect()
 	}
 	
 	pub fn _from_item<'a>(item: &Item, receipes: &'a Vec<Receipe>) -> Option<&'a Receipe> {
 		receipes.iter().filter(|receipe| receipe.result == *item).next()
 	}
 
-	pub fn receipe_match<'a>(items: &'a Vec<Item>, receipes: &'a Vec<Receipe>) -> Vec<&'a Receipe> {
+	pub fn receipe_match<'a>(items: &'a SelectionStorage<Stack>, receipes: &'a Vec<Receipe>) -> Vec<&'a Receipe> {
 		receipes.iter().filter(|receipe| {
 			receipe.incredients.iter().all(|incredient| {
-				items.contains(incredient)
+				items.count(&incredient.item) >= incredient.count
 			})
 		}).collect()
 	}
 }
 
+impl Item {
+	pub fn base_price(&self) -> i32 {
+		match self {
+			Item::PowerConductor => 10,
+			Item::Navcomp => 120,
+			Item::Permit => 50
+		}
+	}
+}
+
 impl ToString for Item {
     fn to_string(&self) -> String {
         format!("{:?}", self)
     }
 }
 
+// a number of the same item kept in one slot
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stack {
+	pub item: Item,
+	pub count: usize
+}
+
+impl Stack {
+	pub fn new(item: Item, count: usize) -> Stack {
+		Stack {
+			item,
+			count
+		}
+	}
+}
+
+impl ToString for Stack {
+    fn to_string(&self) -> String {
+        if self.count == 1 {
+            self.item.to_string()
+        } else {
+            format!("{} x{}", self.item.to_string(), self.count)
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum NpcType {
 	Gnoerf,
+	Human,
 	Guard
 }
 
+// what an npc does while nobody talks to it, schedules refer to named spots of the level by the hour
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Routine {
+	Idle,
+	Patrol(Vec<Position>),
+	Wander(Position, i32),
+	Schedule(Vec<(u64, String)>),
+	Follow,
+	Manning(CrewStation, Position)
+}
+
+// the posts on a ship crew members can be assigned to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum CrewStation {
+	Generator,
+	Console
+}
+
+impl Routine {
+    pub fn is_crew(&self) -> bool {
+        match *self {
+            Routine::Follow | Routine::Manning(_, _) => true,
+            _ => false
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Npc {
 	pub name: String,
@@ -185,7 +256,113 @@ pub struct Npc {
     pub direction: Direction,
     pub look_at: Direction,
     pub dialog: Node<DialogItem>,
-    pub inventory: SelectionStorage<Item>,
+    pub script: Option<String>,
+    pub inventory: SelectionStorage<Stack>,
+    pub credits: i32,
+    pub routine: Routine,
+    pub waypoint: usize,
+    pub ship: Option<String>,
+    pub faction: String,
+    pub memory: Memory,
+    pub stock: Stock,
+    pub post: Option<Post>,
+    pub barks: Vec<Bark>
+}
+
+// a door a guard keeps shut for everyone who meets none of the passes,
+// inside points from the door into the guarded room
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Post {
+    pub door: Position,
+    pub inside: Direction,
+    pub passes: Vec<Condition>,
+    pub refusal: String
+}
+
+impl Post {
+    pub fn is_inside(&self, pos: &Position) -> bool {
+        let offset = pos - &self.door;
+        let inside = self.inside.value();
+        offset.x * inside.x + offset.y * inside.y > 0
+    }
+}
+
+// what a trader keeps on offer and what it buys, the offer refills over universe time
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Stock {
+    pub sells: Vec<(Item, usize)>,
+    pub buys: Vec<Item>,
+    pub restocked: u64
+}
+
+impl Stock {
+    pub fn new(sells: Vec<(Item, usize)>, buys: Vec<Item>) -> Stock {
+        Stock {
+            sells,
+            buys,
+            restocked: 0
+        }
+    }
+
+    pub fn buys(&self, item: &Item) -> bool {
+        self.buys.contains(item)
+    }
+
+    pub fn buys_description(&self) -> String {
+        if self.buys.is_empty() {
+            "buys nothing".to_string()
+        } else {
+            format!("buys {}", self.buys.iter().map(|item| format!("{:?}", item)).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+// what an npc remembers of the player, kept with the npc in the level
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Memory {
+    pub visits: u32,
+    pub chosen: Vec<String>,
+    pub gifts: u32,
+    pub trades: u32,
+    pub reputation: i32,
+    // what the npc sold to the player, selling it back earns no goodwill
+    pub sold: Vec<Item>
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory {
+            visits: 0,
+            chosen: vec![],
+            gifts: 0,
+            trades: 0,
+            reputation: 0,
+            sold: vec![]
+        }
+    }
+
+    // forgets the items that come back and returns how many of them the npc had sold before
+    pub fn take_back(&mut self, items: &[Item]) -> usize {
+        let mut returned = 0;
+        for item in items {
+            if let Some(index) = self.sold.iter().position(|sold| sold == item) {
+                self.sold.remove(index);
+                returned += 1;
+            }
+        }
+
+        returned
+    }
+
+    pub fn remember(&mut self, node: &str) {
+        if !self.has_chosen(node) {
+            self.chosen.push(node.to_string());
+        }
+    }
+
+    pub fn has_chosen(&self, node: &str) -> bool {
+        self.chosen.iter().any(|chosen| chosen == node)
+    }
 }
 
 impl Npc {
@@ -200,6 +377,14 @@ impl Npc {
 					Direction::Right => image_src = "/gnoerf-right.png"
 				}
 			},
+			NpcType::Human => {
+				match self.direction {
+					Direction::Up => image_src = "/human-back.png",
+					Direction::Down => image_src = "/human-front.png",
+					Direction::Left => image_src = "/human-left.png",
+					Direction::Right => image_src = "/human-right.png"
+				}
+			},
 			NpcType::Guard => {
 				match self.direction {
 					Direction::Up => image_src = "/guard-back.png",
@@ -216,7 +401,9 @@ impl Npc {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Storage {
-	pub content: SelectionStorage<Item>,
-	pub face:Direction
+	pub content: SelectionStorage<Stack>,
+	pub face:Direction,
+	// the faction whose guards care about what is taken out, nobody minds for unowned storages
+	pub owner: Option<String>
 }
 
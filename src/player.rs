@@ -1,6 +1,7 @@
 use misc::{Position, Direction};
-use objects::{Item, Terminal};
+use objects::{Item, Stack, Terminal};
 use storage::SelectionStorage;
+use quest::Quest;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct LogEntry {
@@ -20,9 +21,11 @@ pub struct Player {
     pub movement: Vec<Direction>,
     pub direction: Direction,
     pub front_tile: Position,
-    pub inventory: SelectionStorage<Item>,
+    pub inventory: SelectionStorage<Stack>,
+    pub credits: i32,
     pub terminal: Box<Terminal>,
-    pub log:SelectionStorage<LogEntry>
+    pub log:SelectionStorage<LogEntry>,
+    pub quests: Vec<Quest>
 }
 
 impl Player {
@@ -81,12 +84,17 @@ impl Player {
     }
 
     pub fn has(&self, needle: Item) -> bool {
-        for item in self.inventory.iter() {
-            if *item == needle {
-                return true;
-            }
-        }
+        self.inventory.count(&needle) > 0
+    }
+
+    pub fn write_log(&mut self, title: &str, message: &str) {
+        self.log.insert(LogEntry {
+            title: title.to_string(),
+            message: message.to_string()
+        });
+    }
 
-        false
+    pub fn remove(&mut self, needle: Item) -> bool {
+        self.inventory.take(&needle, 1)
     }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+use objects::{Item, Location};
+use misc::Position;
+use world::WorldData;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Objective {
+    Deliver(Item, Location),
+    Power(Location, Position),
+    Talk(String)
+}
+
+impl ToString for Objective {
+    fn to_string(&self) -> String {
+        match *self {
+            Objective::Deliver(ref item, ref location) => format!("deliver {:?} to {}", item, location.to_string()),
+            Objective::Power(ref location, position) => format!("restore power at {} {}", location.to_string(), position.to_string()),
+            Objective::Talk(ref name) => format!("talk to {}", name)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Task {
+    pub objective: Objective,
+    pub done: bool
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Quest {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub tasks: Vec<Task>,
+    pub finished: bool
+}
+
+impl ToString for Quest {
+    fn to_string(&self) -> String {
+        if self.finished {
+            format!("{} (finished)", self.title)
+        } else {
+            self.title.clone()
+        }
+    }
+}
+
+// things happening in the game that may complete an objective
+pub enum QuestEvent {
+    Delivered(Item),
+    Powered,
+    Talked(String)
+}
+
+impl Task {
+    fn completed_by(&self, event: &QuestEvent, data: &WorldData) -> bool {
+        match (&self.objective, event) {
+            (&Objective::Deliver(ref item, ref location), &QuestEvent::Delivered(ref delivered)) => {
+                item == delivered && location == &data.level.location
+            },
+            (&Objective::Power(ref location, position), &QuestEvent::Powered) => {
+                location == &data.level.location && data.level.circuitry.get(position).map(|circuitry| circuitry.powered()).unwrap_or(false)
+            },
+            (&Objective::Talk(ref name), &QuestEvent::Talked(ref talked)) => name == talked,
+            _ => false
+        }
+    }
+}
+
+pub fn start_quest(data: &mut WorldData, id: &str) {
+    if data.level.player.quests.iter().any(|quest| quest.id == id) {
+        return;
+    }
+    let quest = match data.quests.iter().find(|quest| quest.id == id) {
+        Some(quest) => quest.clone(),
+        None => {
+            println!("unknown quest: {}", id);
+            return;
+        }
+    };
+
+    data.level.player.write_log(&quest.title, &quest.description);
+    data.level.player.quests.push(quest);
+    // rooms that already have power count right away
+    notify(data, QuestEvent::Powered);
+}
+
+pub fn complete_quest(data: &mut WorldData, id: &str) {
+    let mut finished = None;
+    if let Some(quest) = data.level.player.quests.iter_mut().find(|quest| quest.id == id && !quest.finished) {
+        for task in quest.tasks.iter_mut() {
+            task.done = true;
+        }
+        quest.finished = true;
+        finished = Some(quest.title.clone());
+    }
+
+    if let Some(title) = finished {
+        data.level.player.write_log(&title, "Finished");
+    }
+}
+
+pub fn quest_active(data: &WorldData, id: &str) -> bool {
+    data.level.player.quests.iter().any(|quest| quest.id == id && !quest.finished)
+}
+
+pub fn quest_finished(data: &WorldData, id: &str) -> bool {
+    data.level.player.quests.iter().any(|quest| quest.id == id && quest.finished)
+}
+
+// marks the objectives an event fulfils, a quest finishes with its last objective
+pub fn notify(data: &mut WorldData, event: QuestEvent) {
+    let mut entries = vec![];
+    let mut quests = data.level.player.quests.clone();
+    for quest in quests.iter_mut().filter(|quest| !quest.finished) {
+        for task in quest.tasks.iter_mut().filter(|task| !task.done) {
+            if task.completed_by(&event, data) {
+                task.done = true;
+                entries.push((quest.title.clone(), format!("Done: {}", task.objective.to_string())));
+            }
+        }
+        if !quest.tasks.is_empty() && quest.tasks.iter().all(|task| task.done) {
+            quest.finished = true;
+            entries.push((quest.title.clone(), "Finished".to_string()));
+        }
+    }
+    data.level.player.quests = quests;
+
+    for (title, message) in entries {
+        data.level.player.write_log(&title, &message);
+    }
+}
@@ -0,0 +1,279 @@
+use std::fs::File;
+use std::io::Read;
+
+use dialog::{DialogItem, DialogAction, DialogGraph, DialogNode, DialogOption, Transition, Condition, Effect, Bark, START_NODE};
+use storage::Node;
+use feature::map::MapFeature;
+use misc::Position;
+use objects::CrewStation;
+use super::universe_file::parse_item;
+
+pub const DIALOG_DIRECTORY: &str = "levels/dialogs";
+// nodes of the common script can be used as snippets by every other script
+pub const COMMON_SCRIPT: &str = "common";
+
+// scripts are read whenever a conversation starts so they can be changed while the game is running,
+// the dialog stored with the npc or terminal is used when the script can not be loaded
+pub fn resolve_dialog(script: &Option<String>, fallback: &Node<DialogItem>) -> DialogGraph {
+    if let Some(ref id) = *script {
+        match load_dialog(id) {
+            Ok(dialog) => return dialog,
+            Err(error) => println!("dialog script not used: {}", error)
+        }
+    }
+
+    DialogGraph::from_tree(fallback)
+}
+
+// barks come from the script and the common script, npcs without a script use their own
+pub fn resolve_barks(script: &Option<String>, fallback: &Vec<Bark>) -> Vec<Bark> {
+    if let Some(ref id) = *script {
+        match load_barks(id) {
+            Ok(barks) => return barks,
+            Err(error) => println!("barks not used: {}", error)
+        }
+    }
+
+    fallback.clone()
+}
+
+pub fn load_barks(id: &str) -> Result<Vec<Bark>, String> {
+    let mut barks = parse_barks(&read_script(id)?)?;
+    if id != COMMON_SCRIPT {
+        if let Ok(common) = read_script(COMMON_SCRIPT) {
+            barks.extend(parse_barks(&common)?);
+        }
+    }
+
+    Ok(barks)
+}
+
+pub fn load_dialog(id: &str) -> Result<DialogGraph, String> {
+    let mut graph = DialogGraph {
+        nodes: parse_script(&read_script(id)?)?
+    };
+    if id != COMMON_SCRIPT {
+        if let Ok(common) = read_script(COMMON_SCRIPT) {
+            for node in parse_script(&common)? {
+                if graph.node(&node.id).is_none() {
+                    graph.nodes.push(node);
+                }
+            }
+        }
+    }
+
+    if graph.node(START_NODE).is_none() {
+        return Err(format!("{}: no node '{}'", id, START_NODE));
+    }
+    for node in graph.nodes.iter() {
+        for option in node.options.iter() {
+            if let Transition::Goto(ref target) = option.transition {
+                if graph.node(target).is_none() {
+                    return Err(format!("{}: node '{}' jumps to unknown node '{}'", id, node.id, target));
+                }
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+fn read_script(id: &str) -> Result<String, String> {
+    let filename = format!("{}/{}.txt", DIALOG_DIRECTORY, id);
+    let mut content = String::new();
+    File::open(&filename)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .map_err(|error| format!("{}: {}", filename, error))?;
+
+    Ok(content)
+}
+
+// a script is a list of nodes:
+//   node <id>
+//   response <text>
+//   action <trade|navigate|view|dock|undock|land|scan|status|intercomm>
+//   effect <effect>
+//   option <text> -> <node id, back or end> | <condition> | ...
+// barks stand on their own and may appear anywhere:
+//   bark <text> | <condition> | ...
+fn parse_script(content: &str) -> Result<Vec<DialogNode>, String> {
+    let mut nodes: Vec<DialogNode> = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, rest) = match line.find(' ') {
+            Some(split) => (&line[..split], line[split + 1..].trim()),
+            None => (line, "")
+        };
+
+        if keyword == "bark" {
+            continue;
+        }
+        if keyword == "node" {
+            nodes.push(DialogNode {
+                id: rest.to_string(),
+                response: "".to_string(),
+                action: None,
+                effects: vec![],
+                options: vec![]
+            });
+            continue;
+        }
+
+        let node = nodes.last_mut().ok_or_else(|| format!("line {}: '{}' outside of a node", index + 1, keyword))?;
+        match keyword {
+            "response" => {
+                node.response = rest.to_string();
+            },
+            "action" => {
+                node.action = Some(parse_action(rest).ok_or_else(|| format!("line {}: unknown action '{}'", index + 1, rest))?);
+            },
+            "effect" => {
+                node.effects.push(parse_effect(rest).ok_or_else(|| format!("line {}: invalid effect '{}'", index + 1, rest))?);
+            },
+            "option" => {
+                let split = rest.find("->").ok_or_else(|| format!("line {}: option without '-> node'", index + 1))?;
+                let mut parts = rest[split + 2..].split('|').map(|part| part.trim());
+                let transition = match parts.next().unwrap_or("") {
+                    "back" => Transition::Back,
+                    "end" => Transition::End,
+                    target => Transition::Goto(target.to_string())
+                };
+                let mut conditions = vec![];
+                for part in parts {
+                    conditions.push(parse_condition(part).ok_or_else(|| format!("line {}: invalid condition '{}'", index + 1, part))?);
+                }
+                node.options.push(DialogOption {
+                    text: rest[..split].trim().to_string(),
+                    transition,
+                    conditions
+                });
+            },
+            _ => {
+                return Err(format!("line {}: unknown keyword '{}'", index + 1, keyword));
+            }
+        }
+    }
+
+    Ok(nodes)
+}
+
+fn parse_barks(content: &str) -> Result<Vec<Bark>, String> {
+    let mut barks = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if !line.starts_with("bark ") {
+            continue;
+        }
+
+        let mut parts = line["bark ".len()..].split('|').map(|part| part.trim());
+        let text = parts.next().unwrap_or("").to_string();
+        let mut conditions = vec![];
+        for part in parts {
+            conditions.push(parse_condition(part).ok_or_else(|| format!("line {}: invalid condition '{}'", index + 1, part))?);
+        }
+        barks.push(Bark {
+            text,
+            conditions
+        });
+    }
+
+    Ok(barks)
+}
+
+fn parse_action(name: &str) -> Option<DialogAction> {
+    match name {
+        "trade" => Some(DialogAction::Trade),
+        "navigate" => Some(DialogAction::Map(MapFeature::Navigate)),
+        "view" => Some(DialogAction::Map(MapFeature::View)),
+        "dock" => Some(DialogAction::Dock),
+        "undock" => Some(DialogAction::Undock),
+        "land" => Some(DialogAction::Land),
+        "scan" => Some(DialogAction::Scan),
+        "status" => Some(DialogAction::Status),
+        "intercomm" => Some(DialogAction::Intercomm),
+        _ => None
+    }
+}
+
+// has <item>, missing <item>, flag <name>, not flag <name>, reputation <faction> <min>, credits <min>,
+// quest <id> (active), finished <id>, chosen <node>, not chosen <node>, liked <min standing>, crew, not crew, aboard, unpowered
+fn parse_condition(entry: &str) -> Option<Condition> {
+    let (keyword, rest) = split_keyword(entry);
+    match keyword {
+        "has" => parse_item(rest).map(Condition::HasItem),
+        "missing" => parse_item(rest).map(Condition::MissingItem),
+        "flag" => Some(Condition::Flag(rest.to_string())),
+        "not" if rest.starts_with("flag ") => Some(Condition::NotFlag(rest["flag ".len()..].trim().to_string())),
+        "reputation" => split_amount(rest).map(|(faction, amount)| Condition::Reputation(faction, amount)),
+        "credits" => rest.parse().ok().map(Condition::Credits),
+        "quest" => Some(Condition::QuestActive(rest.to_string())),
+        "finished" => Some(Condition::QuestFinished(rest.to_string())),
+        "chosen" => Some(Condition::Chosen(rest.to_string())),
+        "not" if rest.starts_with("chosen ") => Some(Condition::NotChosen(rest["chosen ".len()..].trim().to_string())),
+        "liked" => rest.parse().ok().map(Condition::Liked),
+        "crew" => Some(Condition::Crew),
+        "not" if rest == "crew" => Some(Condition::NotCrew),
+        "aboard" => Some(Condition::Aboard),
+        "unpowered" => Some(Condition::Unpowered),
+        _ => None
+    }
+}
+
+// give <item>, take <item>, set <flag>, clear <flag>, open <x> <y>, unlock <x> <y>,
+// quest <id>, complete <id>, credits <amount>, reputation <faction> <amount>, like <amount>,
+// recruit, follow, assign <generator|console>, dismiss
+fn parse_effect(entry: &str) -> Option<Effect> {
+    let (keyword, rest) = split_keyword(entry);
+    match keyword {
+        "give" => parse_item(rest).map(Effect::GiveItem),
+        "take" => parse_item(rest).map(Effect::TakeItem),
+        "set" => Some(Effect::SetFlag(rest.to_string())),
+        "clear" => Some(Effect::ClearFlag(rest.to_string())),
+        "open" => parse_position(rest).map(Effect::OpenDoor),
+        "unlock" => parse_position(rest).map(Effect::UnlockDoor),
+        "quest" => Some(Effect::StartQuest(rest.to_string())),
+        "complete" => Some(Effect::CompleteQuest(rest.to_string())),
+        "credits" => rest.parse().ok().map(Effect::Credits),
+        "reputation" => split_amount(rest).map(|(faction, amount)| Effect::Reputation(faction, amount)),
+        "like" => rest.parse().ok().map(Effect::Like),
+        "recruit" => Some(Effect::Recruit),
+        "follow" => Some(Effect::Follow),
+        "assign" => match rest {
+            "generator" => Some(Effect::Assign(CrewStation::Generator)),
+            "console" => Some(Effect::Assign(CrewStation::Console)),
+            _ => None
+        },
+        "dismiss" => Some(Effect::Dismiss),
+        _ => None
+    }
+}
+
+fn split_keyword(entry: &str) -> (&str, &str) {
+    let entry = entry.trim();
+    match entry.find(' ') {
+        Some(split) => (&entry[..split], entry[split + 1..].trim()),
+        None => (entry, "")
+    }
+}
+
+// faction names may contain spaces, the amount is always the last word
+fn split_amount(entry: &str) -> Option<(String, i32)> {
+    let split = entry.rfind(' ')?;
+    let amount = entry[split + 1..].parse().ok()?;
+
+    Some((entry[..split].trim().to_string(), amount))
+}
+
+fn parse_position(entry: &str) -> Option<Position> {
+    let mut coordinates = entry.split_whitespace().map(|value| value.parse::<i32>());
+    match (coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y))) => Some(Position::new(x, y)),
+        _ => None
+    }
+}
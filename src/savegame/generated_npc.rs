@@ -0,0 +1,156 @@
+use rand::Rng;
+
+use objects::*;
+use storage::{SelectionStorage, Node};
+use dialog::*;
+use misc::Direction;
+use constants::RECRUIT_LIKING;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Role {
+    Trader,
+    Mechanic,
+    Guard
+}
+
+// every species builds its names from its own sounds
+const GNOERF_SYLLABLES: [&str; 10] = ["gno", "erf", "bru", "mok", "gli", "af", "nor", "uk", "fle", "rb"];
+const HUMAN_SYLLABLES: [&str; 12] = ["an", "na", "jo", "el", "mar", "ta", "ri", "ko", "sa", "len", "do", "vi"];
+
+const TRADER_GREETINGS: [&str; 3] = [
+    "{name} here, best prices on {station}",
+    "Welcome, traveller. {name} trades with anyone who pays",
+    "Buying and selling, {faction} credit is good here"
+];
+const MECHANIC_GREETINGS: [&str; 3] = [
+    "{name}, I keep the wires of {station} humming",
+    "Mind the cables. I fix what the {faction} breaks",
+    "Power conductors burn out all the time, I always need spares"
+];
+const GUARD_GREETINGS: [&str; 3] = [
+    "{name}, {faction} security. Behave",
+    "Keep your hands off what is not yours",
+    "Quiet shift on {station}, let us keep it that way"
+];
+
+// guards wear the uniform over a human body
+fn random_name<R: Rng>(rng: &mut R, species: &NpcType) -> String {
+    let syllables: &[&str] = match *species {
+        NpcType::Gnoerf => &GNOERF_SYLLABLES,
+        NpcType::Human | NpcType::Guard => &HUMAN_SYLLABLES
+    };
+    let mut name = String::new();
+    for _ in 0..rng.gen_range(2, 4) {
+        name.push_str(syllables[rng.gen_range(0, syllables.len())]);
+    }
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => name
+    }
+}
+
+fn line(text: &str, response: &str, action: Option<DialogAction>) -> Node<DialogItem> {
+    crew_line(text, response, action, vec![], vec![])
+}
+
+fn crew_line(text: &str, response: &str, action: Option<DialogAction>, conditions: Vec<Condition>, effects: Vec<Effect>) -> Node<DialogItem> {
+    Node {
+        value: DialogItem {
+            text: text.to_string(),
+            response: response.to_string(),
+            action,
+            conditions,
+            effects
+        },
+        children: SelectionStorage::new()
+    }
+}
+
+// a resident of a generated station, the species decides the sprite and name,
+// the role the goods and what the npc talks about
+pub fn generated_npc<R: Rng>(rng: &mut R, role: &Role, station: &str, faction: &str, direction: Direction) -> Npc {
+    let variant = if *role == Role::Guard {
+        NpcType::Guard
+    } else if rng.gen_bool(0.5) {
+        NpcType::Gnoerf
+    } else {
+        NpcType::Human
+    };
+    let name = random_name(rng, &variant);
+
+    let (greetings, credits, stock) = match *role {
+        Role::Trader => {
+            let sells = vec![(Item::PowerConductor, rng.gen_range(1, 5)), (Item::Navcomp, rng.gen_range(0, 2))];
+            (&TRADER_GREETINGS, rng.gen_range(150, 300), Stock::new(sells, vec![Item::PowerConductor, Item::Navcomp]))
+        },
+        Role::Mechanic => {
+            (&MECHANIC_GREETINGS, rng.gen_range(40, 120), Stock::new(vec![(Item::PowerConductor, rng.gen_range(2, 6))], vec![Item::PowerConductor]))
+        },
+        Role::Guard => (&GUARD_GREETINGS, 0, Stock::new(vec![], vec![]))
+    };
+    let barks = match *role {
+        Role::Trader => vec![
+            Bark { text: "Fresh goods, come have a look".to_string(), conditions: vec![] },
+            Bark { text: "I pay well for navcomps".to_string(), conditions: vec![Condition::HasItem(Item::Navcomp)] }
+        ],
+        Role::Mechanic => vec![
+            Bark { text: "Power is out again, where is my toolkit".to_string(), conditions: vec![Condition::Unpowered] },
+            Bark { text: "Hear that hum? Sounds healthy".to_string(), conditions: vec![] }
+        ],
+        Role::Guard => vec![
+            Bark { text: "Move along".to_string(), conditions: vec![] },
+            Bark { text: "Papers in order, I see".to_string(), conditions: vec![Condition::HasItem(Item::Permit)] }
+        ]
+    };
+    let greeting = greetings[rng.gen_range(0, greetings.len())]
+        .replace("{name}", &name)
+        .replace("{station}", station)
+        .replace("{faction}", faction);
+
+    let mut dialog = SelectionStorage::new();
+    dialog.insert(line("Hi", &greeting, None));
+    if *role != Role::Guard {
+        dialog.insert(line("Trade", "Here are my goods", Some(DialogAction::Trade)));
+        // residents who like the player can be talked into joining the crew
+        dialog.insert(crew_line("Join my crew", "Why not, lead the way", None, vec![Condition::NotCrew, Condition::Liked(RECRUIT_LIKING)], vec![Effect::Recruit]));
+        dialog.insert(crew_line("Follow me", "Right behind you", None, vec![Condition::Crew], vec![Effect::Follow]));
+        dialog.insert(crew_line("Take the generator", "I will keep the fuel burning clean", None, vec![Condition::Crew, Condition::Aboard], vec![Effect::Assign(CrewStation::Generator)]));
+        dialog.insert(crew_line("Take the console", "I will plot shorter jumps", None, vec![Condition::Crew, Condition::Aboard], vec![Effect::Assign(CrewStation::Console)]));
+        dialog.insert(crew_line("Leave the crew", "It was a good time", None, vec![Condition::Crew], vec![Effect::Dismiss]));
+    }
+    dialog.insert(line("Bye", "Goodbye", None));
+
+    let mut inventory = SelectionStorage::new();
+    for &(item, amount) in stock.sells.iter() {
+        inventory.add(item, amount);
+    }
+
+    Npc {
+        name,
+        variant,
+        direction,
+        look_at: direction,
+        dialog: Node {
+            value: DialogItem {
+                text: "".to_string(),
+                response: "...".to_string(),
+                action: None,
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: dialog
+        },
+        script: None,
+        inventory,
+        credits,
+        routine: Routine::Idle,
+        waypoint: 0,
+        ship: None,
+        faction: faction.to_string(),
+        memory: Memory::new(),
+        stock,
+        post: None,
+        barks
+    }
+}
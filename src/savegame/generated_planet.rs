@@ -0,0 +1,174 @@
+use rand::Rng;
+
+use world::WorldData;
+use objects::*;
+use misc::*;
+use savegame::generated_universe::{seeded_rng, location_seed};
+
+const SURFACE_WIDTH: i32 = 28;
+const SURFACE_HEIGHT: i32 = 18;
+const PLACEMENT_ATTEMPTS: usize = 100;
+
+pub fn planet_surface(data: &mut WorldData, planet_id: &str) {
+    data.level.clear();
+    data.level.backdrop = String::from("");
+    data.level.location = Location::Planet(planet_id.to_string());
+
+    let mut rng = seeded_rng(location_seed(data.universe.seed, planet_id));
+    let mut occupied: Vec<Position> = vec![];
+
+    // terrain covers the whole surface, the border is impassable rock
+    let mut floor = vec![];
+    let mut walls = vec![];
+    for y in 0..SURFACE_HEIGHT + 2 {
+        for x in 0..SURFACE_WIDTH + 2 {
+            floor.push((x, y, FloorType::Soil));
+            if x == 0 || y == 0 || x == SURFACE_WIDTH + 1 || y == SURFACE_HEIGHT + 1 {
+                walls.push((x, y, WallType::Rock, Direction::Down));
+            }
+        }
+    }
+    for _ in 0..rng.gen_range(3, 7) {
+        let center = random_surface_position(&mut rng);
+        let radius = rng.gen_range(2, 5) as f32;
+        for tile in floor.iter_mut() {
+            if center.dist(&Position::new(tile.0, tile.1)) <= radius && rng.gen_bool(0.8) {
+                tile.2 = FloorType::Gravel;
+            }
+        }
+    }
+
+    // landing pad with a powered exit back to the ship
+    let pad = Position::new(
+        rng.gen_range(3, SURFACE_WIDTH - 4),
+        rng.gen_range(4, SURFACE_HEIGHT - 3)
+    );
+    for y in pad.y - 2..pad.y + 3 {
+        for x in pad.x - 2..pad.x + 3 {
+            occupied.push(Position::new(x, y));
+        }
+    }
+    for tile in floor.iter_mut() {
+        if (tile.0 - pad.x).abs() <= 1 && (tile.1 - pad.y).abs() <= 1 {
+            tile.2 = FloorType::LandingPad;
+        }
+    }
+    walls.push((pad.x - 1, pad.y - 2, WallType::Rock, Direction::Down));
+
+    super::insert_floor(data, floor);
+
+    let mut caches = vec![];
+    for _ in 0..rng.gen_range(2, 5) {
+        if rng.gen_bool(0.5) {
+            if let Some(ruin) = place(&mut rng, &mut occupied, 4, 4) {
+                walls.push((ruin.x, ruin.y, WallType::Corner, Direction::Down));
+                walls.push((ruin.x + 3, ruin.y, WallType::Corner, Direction::Left));
+                walls.push((ruin.x, ruin.y + 3, WallType::Corner, Direction::Right));
+                walls.push((ruin.x + 3, ruin.y + 3, WallType::Corner, Direction::Up));
+                walls.push((ruin.x + 1, ruin.y, WallType::Wall, Direction::Down));
+                walls.push((ruin.x + 2, ruin.y, WallType::Window, Direction::Down));
+                walls.push((ruin.x, ruin.y + 1, WallType::Wall, Direction::Right));
+                walls.push((ruin.x, ruin.y + 2, WallType::Wall, Direction::Right));
+                walls.push((ruin.x + 3, ruin.y + 1, WallType::Wall, Direction::Left));
+                walls.push((ruin.x + 3, ruin.y + 2, WallType::Wall, Direction::Left));
+                walls.push((ruin.x + 1, ruin.y + 3, WallType::Wall, Direction::Up));
+                super::insert_floor(data, vec![
+                    (ruin.x + 1, ruin.y + 1, FloorType::Regular),
+                    (ruin.x + 2, ruin.y + 1, FloorType::Regular),
+                    (ruin.x + 1, ruin.y + 2, FloorType::Regular),
+                    (ruin.x + 2, ruin.y + 2, FloorType::Regular),
+                    (ruin.x + 2, ruin.y + 3, FloorType::Regular)
+                ]);
+                caches.push((ruin.x + 1, ruin.y + 1, Direction::Right));
+            }
+        } else if let Some(cache) = place(&mut rng, &mut occupied, 1, 1) {
+            caches.push((cache.x, cache.y, Direction::Down));
+        }
+    }
+
+    for _ in 0..rng.gen_range(4, 9) {
+        let mut outcrop = random_surface_position(&mut rng);
+        for _ in 0..rng.gen_range(1, 5) {
+            if !occupied.contains(&outcrop) && is_on_surface(outcrop) {
+                occupied.push(outcrop);
+                walls.push((outcrop.x, outcrop.y, WallType::Rock, Direction::Down));
+            }
+            let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+            outcrop = &outcrop + &rng.choose(&directions).unwrap().value();
+        }
+    }
+
+    super::insert_walls(data, walls);
+
+    super::insert_storage(data, caches.clone());
+    for cache in caches {
+        if let Some(storage) = data.level.storages.get_mut(Position::new(cache.0, cache.1)) {
+            for _ in 0..rng.gen_range(1, 4) {
+                if rng.gen_bool(0.15) {
+                    storage.content.add(Item::Navcomp, 1);
+                } else {
+                    storage.content.add(Item::PowerConductor, 1);
+                }
+            }
+        }
+    }
+
+    super::insert_generator(data, vec![
+        (pad.x + 1, pad.y - 2, Direction::Down)
+    ]);
+
+    super::insert_circuitry(data, vec![
+        (pad.x, pad.y - 2, true),
+        (pad.x + 1, pad.y - 2, true)
+    ]);
+
+    data.level.update_power();
+
+    // the destination follows the ship landed here, see Level::update_exits
+    super::insert_doors(data, vec![
+        (pad.x, pad.y - 2, DoorStatus::Closed, DoorType::Exit(Location::Space), Direction::Down)
+    ]);
+
+    data.level.update_exits(&data.universe);
+
+    super::insert_player(data, (pad.x, pad.y), Direction::Up, vec![]);
+
+    data.levels.insert(data.level.location.clone(), data.level.clone());
+
+    println!("game loaded: generated planet {}", planet_id);
+}
+
+fn random_surface_position<R: Rng>(rng: &mut R) -> Position {
+    Position::new(
+        rng.gen_range(1, SURFACE_WIDTH + 1),
+        rng.gen_range(1, SURFACE_HEIGHT + 1)
+    )
+}
+
+fn is_on_surface(pos: Position) -> bool {
+    pos.x >= 1 && pos.y >= 1 && pos.x <= SURFACE_WIDTH && pos.y <= SURFACE_HEIGHT
+}
+
+// finds a free area of the given size keeping a tile of space around it
+fn place<R: Rng>(rng: &mut R, occupied: &mut Vec<Position>, width: i32, height: i32) -> Option<Position> {
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        let origin = Position::new(
+            rng.gen_range(2, SURFACE_WIDTH - width),
+            rng.gen_range(2, SURFACE_HEIGHT - height)
+        );
+
+        let mut area = vec![];
+        for y in origin.y - 1..origin.y + height + 1 {
+            for x in origin.x - 1..origin.x + width + 1 {
+                area.push(Position::new(x, y));
+            }
+        }
+
+        if area.iter().all(|pos| !occupied.contains(pos)) {
+            occupied.extend(area);
+            return Some(origin);
+        }
+    }
+
+    None
+}
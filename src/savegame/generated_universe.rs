@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand::prng::ChaChaRng;
+
+use objects::{Location, Memory};
+use misc::Position;
+use objects::Item;
+use world::{Universe, Sector, Ship, Station, Planet, Orders, Site, Market, Goods};
+use constants::{FUEL_CAPACITY, LAYOVER_TICKS, START_TIME};
+
+const SECTOR_COUNT: (usize, usize) = (5, 9);
+const SECTOR_SPACING: f32 = 5.0;
+const UNIVERSE_WIDTH: i32 = 24;
+const UNIVERSE_HEIGHT: i32 = 14;
+const PLACEMENT_ATTEMPTS: usize = 200;
+
+const SYLLABLES: [&str; 24] = [
+    "ka", "vo", "ri", "mun", "tel", "os", "ar", "ne",
+    "lu", "xa", "dor", "ik", "sel", "ta", "gor", "pha",
+    "en", "zu", "bri", "ol", "qua", "ven", "th", "is"
+];
+const STATION_SUFFIXES: [&str; 4] = ["Outpost", "Hub", "Relay", "Dock"];
+const PLANET_NUMERALS: [&str; 4] = ["I", "II", "III", "IV"];
+const DERELICT_PREFIXES: [&str; 3] = ["Wreck", "Hulk", "Derelict"];
+const TRADER_PREFIXES: [&str; 3] = ["Hauler", "Barge", "Freighter"];
+const PATROL_PREFIXES: [&str; 2] = ["Cutter", "Sentinel"];
+const PATROL_STOPS: usize = 3;
+const TRADE_GOODS: [Item; 2] = [Item::PowerConductor, Item::Navcomp];
+const FACTIONS: [&str; 4] = ["Sol Union", "Free Traders", "Mining Guild", "Independent"];
+
+pub fn seeded_rng(seed: u64) -> ChaChaRng {
+    let mut bytes = [0u8; 32];
+    for (index, byte) in bytes.iter_mut().take(8).enumerate() {
+        *byte = (seed >> (index * 8)) as u8;
+    }
+
+    ChaChaRng::from_seed(bytes)
+}
+
+// derives a stable seed for a single location so its level can be regenerated from the universe seed
+pub fn location_seed(seed: u64, id: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in id.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    seed ^ hash
+}
+
+pub fn generated_universe(seed: u64) -> Universe {
+    let mut rng = seeded_rng(seed);
+    let mut names: Vec<String> = vec![];
+
+    let mut universe = Universe {
+        sectors: vec![],
+        stations: vec![],
+        ships: vec![],
+        planets: vec![],
+        player_location: Location::Ship("Tech 2.1".to_string()),
+        player_ship: "Tech 2.1".to_string(),
+        seed,
+        discovered: vec![],
+        time: Duration::from_secs(START_TIME),
+        sites: vec![
+            Site {
+                location: Location::Ship("Tech 2.1".to_string()),
+                level: None,
+                backdrop: Some("/tech-2-1.png".to_string())
+            }
+        ],
+        flags: vec![],
+        reputation: vec![]
+    };
+
+    let sector_count = rng.gen_range(SECTOR_COUNT.0, SECTOR_COUNT.1 + 1);
+    let mut occupied: Vec<Position> = vec![];
+    for _ in 0..PLACEMENT_ATTEMPTS {
+        if universe.sectors.len() == sector_count {
+            break;
+        }
+
+        let position = Position::new(
+            rng.gen_range(-UNIVERSE_WIDTH / 2, UNIVERSE_WIDTH / 2),
+            rng.gen_range(-UNIVERSE_HEIGHT / 2, UNIVERSE_HEIGHT / 2)
+        );
+        if universe.sectors.iter().any(|sector| sector.position.dist(&position) < SECTOR_SPACING) {
+            continue;
+        }
+
+        let name = unique_name(&mut rng, &mut names);
+        let faction = FACTIONS[rng.gen_range(0, FACTIONS.len())];
+        occupied.push(position);
+
+        let mut suffixes = STATION_SUFFIXES.to_vec();
+        rng.shuffle(&mut suffixes);
+        for suffix in suffixes.iter().take(rng.gen_range(1, 3)) {
+            let mut market = Market::new();
+            for item in TRADE_GOODS.iter() {
+                market.goods.push(Goods {
+                    item: *item,
+                    supply: rng.gen_range(0, 5),
+                    demand: rng.gen_range(0, 5)
+                });
+            }
+            universe.stations.push(Station {
+                id: format!("{} {}", name, suffix),
+                position,
+                faction: faction.to_string(),
+                market
+            });
+        }
+
+        for index in 0..rng.gen_range(0, PLANET_NUMERALS.len() + 1) {
+            universe.planets.push(Planet {
+                id: format!("{} {}", name, PLANET_NUMERALS[index]),
+                position
+            });
+        }
+
+        // the first sector is where the player starts and stays safe
+        let hazardous = !universe.sectors.is_empty() && rng.gen_bool(0.25);
+        universe.sectors.push(Sector {
+            id: name,
+            position,
+            hazardous
+        });
+    }
+
+    // derelicts drift in the empty space around sectors
+    let sector_positions: Vec<Position> = universe.sectors.iter().map(|sector| sector.position).collect();
+    for sector_position in sector_positions {
+        if !rng.gen_bool(0.5) {
+            continue;
+        }
+
+        for _ in 0..PLACEMENT_ATTEMPTS {
+            let position = Position::new(
+                sector_position.x + rng.gen_range(-2, 3),
+                sector_position.y + rng.gen_range(-2, 3)
+            );
+            if occupied.contains(&position) {
+                continue;
+            }
+
+            let name = unique_name(&mut rng, &mut names);
+            let prefix = DERELICT_PREFIXES[rng.gen_range(0, DERELICT_PREFIXES.len())];
+            occupied.push(position);
+            universe.ships.push(Ship {
+                id: format!("{} {}", prefix, name),
+                position,
+                docked: Location::Space,
+                faction: "Unclaimed".to_string(),
+                target: None,
+                route: vec![],
+                fuel: 0,
+                orders: Orders::Idle,
+                layover: 0,
+                crew: vec![],
+                pilot_memory: Memory::new()
+            });
+            break;
+        }
+    }
+
+    // traders and patrols start docked, patrols fly between the stations closest to their home
+    let mut homes: Vec<(String, Position, String)> = vec![];
+    for station in universe.stations.iter() {
+        homes.push((station.id.clone(), station.position, station.faction.clone()));
+    }
+    for &(ref station_id, position, ref faction) in homes.iter() {
+        if !rng.gen_bool(0.6) {
+            continue;
+        }
+
+        let orders = if rng.gen_bool(0.5) {
+            Orders::Trade
+        } else {
+            let mut nearest = homes.clone();
+            nearest.sort_by_key(|&(_, other, _)| (position.dist(&other) * 10.0) as i32);
+            Orders::Patrol(nearest.into_iter().take(PATROL_STOPS).map(|(id, _, _)| id).collect())
+        };
+        let prefix = match orders {
+            Orders::Trade => TRADER_PREFIXES[rng.gen_range(0, TRADER_PREFIXES.len())],
+            _ => PATROL_PREFIXES[rng.gen_range(0, PATROL_PREFIXES.len())]
+        };
+        let name = unique_name(&mut rng, &mut names);
+        universe.ships.push(Ship {
+            id: format!("{} {}", prefix, name),
+            position,
+            docked: Location::Station(station_id.clone()),
+            faction: faction.clone(),
+            target: None,
+            route: vec![],
+            fuel: FUEL_CAPACITY,
+            orders,
+            layover: rng.gen_range(0, LAYOVER_TICKS + 1),
+            crew: vec![],
+            pilot_memory: Memory::new()
+        });
+    }
+
+    let mut start = None;
+    if let Some(station) = universe.stations.first() {
+        start = Some((station.position, Location::Station(station.id.clone())));
+    }
+    let (position, docked) = start.unwrap_or((Position::new(0, 0), Location::Space));
+    universe.ships.push(Ship {
+        id: "Tech 2.1".to_string(),
+        position,
+        docked,
+        faction: "Independent".to_string(),
+        target: None,
+        route: vec![],
+        fuel: FUEL_CAPACITY,
+        orders: Orders::Idle,
+        layover: 0,
+        crew: vec![],
+        pilot_memory: Memory::new()
+    });
+    universe.discover(position);
+
+    println!("universe generated from seed {}", seed);
+
+    universe
+}
+
+fn unique_name<R: Rng>(rng: &mut R, names: &mut Vec<String>) -> String {
+    loop {
+        let mut name = String::new();
+        for _ in 0..rng.gen_range(2, 4) {
+            name.push_str(SYLLABLES[rng.gen_range(0, SYLLABLES.len())]);
+        }
+        let mut chars = name.chars();
+        let name = match chars.next() {
+            Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+            None => continue
+        };
+
+        if !names.contains(&name) {
+            names.push(name.clone());
+            return name;
+        }
+    }
+}
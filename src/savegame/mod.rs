@@ -11,9 +11,16 @@ use player::Player;
 use objects::*;
 use misc::{Position, Direction};
 use storage::{Node, SelectionStorage};
+use constants::{STARTING_CREDITS, CIRCUITRY_CONDUCTORS};
 
 pub mod static_levels;
 pub mod static_npc;
+pub mod generated_universe;
+pub mod generated_planet;
+pub mod generated_npc;
+pub mod universe_file;
+pub mod dialog_file;
+pub mod quest_file;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Save {
@@ -89,8 +96,17 @@ pub fn save_location(world: &mut WorldData) {
 
 pub fn load_location(world: &mut WorldData, location: &Location) {
     let mut loaded = false;
+    let visited = world.levels.contains_key(location);
     let inventory = world.level.player.inventory.clone();
+    let credits = world.level.player.credits;
+    let log = world.level.player.log.clone();
+    let quests = world.level.player.quests.clone();
     let direction = world.level.player.direction.clone();
+    let previous = world.level.location.clone();
+    let followers = world.level.take_followers();
+    if let Some(level) = world.levels.get_mut(&previous) {
+        level.take_followers();
+    }
 
     {
         if let Some(level) = world.levels.get(location) {
@@ -102,10 +118,10 @@ pub fn load_location(world: &mut WorldData, location: &Location) {
     if !loaded {
         match location {
             Location::Ship(id) => {
-                let file = format!("levels/{}.ship.tar", id);
+                let file = level_file(world, location, "ship");
                 if load_level(world, &file).is_err() {
-                    if id == "Tech 2.1" {
-                        static_levels::static_ship_tech(world);
+                    if *id == world.universe.player_ship {
+                        static_levels::static_ship_tech(world, id);
                     } else {
                         static_levels::empty(world);
                         world.level.location = location.clone();
@@ -113,10 +129,10 @@ pub fn load_location(world: &mut WorldData, location: &Location) {
                 }
             },
             Location::Station(id) => {
-                let file = format!("levels/{}.station.tar", id);
+                let file = level_file(world, location, "station");
                 if load_level(world, &file).is_err() {
-                    if id == "Mun" {
-                        static_levels::static_station_outpost(world);
+                    if world.universe.station(id).is_some() {
+                        static_levels::static_station_outpost(world, id);
                     } else {
                         static_levels::empty(world);
                         world.level.location = location.clone();
@@ -124,13 +140,52 @@ pub fn load_location(world: &mut WorldData, location: &Location) {
                 }
             },
             Location::Space => static_levels::empty(world),
-            Location::Planet(_) => static_levels::empty(world),
+            Location::Planet(id) => {
+                if world.universe.planet(id).is_some() {
+                    generated_planet::planet_surface(world, id);
+                } else {
+                    static_levels::empty(world);
+                    world.level.location = location.clone();
+                }
+            },
         }
     }
 
+    let backdrop = world.universe.backdrop(location);
+    if backdrop != "" {
+        world.level.backdrop = backdrop;
+    }
+    world.level.update_exits(&world.universe);
+    world.level.update_visitors(&mut world.universe);
     world.universe.player_location = world.level.location.clone();
+    if let Some(pos) = world.universe.player_position() {
+        world.universe.discover(pos);
+    }
     world.level.player.inventory = inventory;
+    world.level.player.credits = credits;
+    world.level.player.log = log;
+    world.level.player.quests = quests;
+    if !visited {
+        world.level.player.write_log(&location.to_string(), "Visited for the first time");
+    }
     world.level.player.direction = direction;
+    // followers that found no room here wait in the saved copy of the previous level
+    let stranded = world.level.place_followers(followers);
+    for (origin, follower) in stranded {
+        world.level.player.write_log(&follower.name, "Stayed behind, there was no room to follow");
+        if let Some(level) = world.levels.get_mut(&previous) {
+            level.npc.insert(origin, follower);
+        }
+    }
+    world.bubbles.clear();
+}
+
+// a site in the universe file can name the level, otherwise it is looked up by id and kind
+fn level_file(world: &WorldData, location: &Location, kind: &str) -> String {
+    match world.universe.site(location).and_then(|site| site.level.clone()) {
+        Some(level) => level,
+        None => format!("levels/{}.{}.tar", location.to_string(), kind)
+    }
 }
 
 fn load_level(world: &mut WorldData, filename: &str) -> Result<(), io::Error> {
@@ -158,6 +213,13 @@ fn load_level(world: &mut WorldData, filename: &str) -> Result<(), io::Error> {
 
         world.level.update_power();
     }
+
+    // level files may have been saved before their npcs' scripts changed
+    for npc in world.level.npc.iter_mut() {
+        if let Some(ref mut npc) = *npc {
+            npc.barks = dialog_file::resolve_barks(&npc.script, &npc.barks);
+        }
+    }
     println!("location loaded from file: {}", filename);
 
     Ok(())
@@ -201,7 +263,7 @@ pub fn insert_pilot_seat(world: &mut WorldData, pilot_seats: Vec<(i32, i32, Dire
 
 pub fn insert_circuitry(world: &mut WorldData, circuitry: Vec<(i32, i32, bool)>) {
     let mut default_parts = SelectionStorage::new();
-    default_parts.insert(Item::PowerConductor);
+    default_parts.add(Item::PowerConductor, CIRCUITRY_CONDUCTORS);
     for circuit in circuitry {
         let parts;
         if circuit.2 {
@@ -220,7 +282,7 @@ pub fn insert_storage(world: &mut WorldData, storages: Vec<(i32, i32, Direction)
     for storage in storages {
         world.level.storages.insert(
             Position { x: storage.0, y: storage.1 },
-            Storage { content: SelectionStorage::new(), face: storage.2 }
+            Storage { content: SelectionStorage::new(), face: storage.2, owner: None }
         );
     }
 }
@@ -237,9 +299,9 @@ pub fn insert_doors(world: &mut WorldData, doors: Vec<(i32, i32, DoorStatus, Doo
 pub fn insert_player(world: &mut WorldData, pos: (i32, i32), dir: Direction, inv: Vec<Item>) {
     let player_position = Position::new(pos.0, pos.1);
     let player_front_tile = &dir.value() + &player_position;
-    let mut inventory = <SelectionStorage<Item>>::new();
+    let mut inventory = <SelectionStorage<Stack>>::new();
     for item in inv {
-        inventory.insert(item);
+        inventory.add(item, 1);
     }
     let player = Player {
         position: player_position,
@@ -247,17 +309,22 @@ pub fn insert_player(world: &mut WorldData, pos: (i32, i32), dir: Direction, inv
         direction: dir,
         front_tile: player_front_tile,
         inventory,
+        credits: STARTING_CREDITS,
         terminal: Box::new(Terminal {
             variant: TerminalType::Hud,
             dialog: Node::new(),
+            script: None,
             front: Direction::Down
         }),
-        log: SelectionStorage::new()
+        log: SelectionStorage::new(),
+        quests: vec![]
     };
     world.level.player = player;
 }
 
-pub fn insert_npc(world: &mut WorldData, x: i32, y: i32, npc: Npc) {
+// barks of scripted npcs are read once when the npc is placed
+pub fn insert_npc(world: &mut WorldData, x: i32, y: i32, mut npc: Npc) {
+    npc.barks = dialog_file::resolve_barks(&npc.script, &npc.barks);
     world.level.npc.insert(
         Position { x, y },
         npc
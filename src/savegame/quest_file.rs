@@ -0,0 +1,90 @@
+use std::fs::File;
+use std::io::Read;
+
+use quest::{Quest, Task, Objective};
+use objects::Location;
+use misc::Position;
+use super::universe_file::parse_item;
+
+pub const QUEST_FILE: &str = "levels/quests.txt";
+
+pub fn load_quests() -> Vec<Quest> {
+    match read_quests(QUEST_FILE) {
+        Ok(quests) => quests,
+        Err(error) => {
+            println!("quest file not used: {}", error);
+            vec![]
+        }
+    }
+}
+
+pub fn read_quests(filename: &str) -> Result<Vec<Quest>, String> {
+    let mut content = String::new();
+    File::open(filename)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .map_err(|error| format!("{}: {}", filename, error))?;
+
+    parse_quests(&content)
+}
+
+// a quest line is followed by its objectives:
+//   quest | id | title | description
+//   deliver | item | station
+//   power | station, ship or planet | id | x | y
+//   talk | npc name
+pub fn parse_quests(content: &str) -> Result<Vec<Quest>, String> {
+    let mut quests: Vec<Quest> = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+        if fields[0] == "quest" {
+            if fields.len() < 4 {
+                return Err(format!("line {}: expected quest | id | title | description", index + 1));
+            }
+            quests.push(Quest {
+                id: fields[1].to_string(),
+                title: fields[2].to_string(),
+                description: fields[3].to_string(),
+                tasks: vec![],
+                finished: false
+            });
+            continue;
+        }
+
+        let objective = match (fields[0], fields.len()) {
+            ("deliver", 3) => {
+                let item = parse_item(fields[1]).ok_or_else(|| format!("line {}: unknown item '{}'", index + 1, fields[1]))?;
+                Objective::Deliver(item, Location::Station(fields[2].to_string()))
+            },
+            ("power", 5) => {
+                let id = fields[2].to_string();
+                let location = match fields[1] {
+                    "station" => Location::Station(id),
+                    "ship" => Location::Ship(id),
+                    "planet" => Location::Planet(id),
+                    kind => return Err(format!("line {}: unknown location kind '{}'", index + 1, kind))
+                };
+                let x = fields[3].parse().map_err(|_| format!("line {}: invalid x '{}'", index + 1, fields[3]))?;
+                let y = fields[4].parse().map_err(|_| format!("line {}: invalid y '{}'", index + 1, fields[4]))?;
+                Objective::Power(location, Position::new(x, y))
+            },
+            ("talk", 2) => Objective::Talk(fields[1].to_string()),
+            _ => {
+                return Err(format!("line {}: unknown objective '{}'", index + 1, line));
+            }
+        };
+
+        let quest = quests.last_mut().ok_or_else(|| format!("line {}: objective outside of a quest", index + 1))?;
+        quest.tasks.push(Task {
+            objective,
+            done: false
+        });
+    }
+
+    Ok(quests)
+}
@@ -1,11 +1,20 @@
+use std::time::Duration;
+
+use rand::Rng;
+
 use world::WorldData;
 use objects::*;
 use misc::*;
 use savegame::static_npc::*;
+use savegame::generated_npc::{generated_npc, Role};
+use savegame::generated_universe::{seeded_rng, location_seed};
 use storage::{Node, SelectionStorage};
 use dialog::*;
 use feature::map::MapFeature;
-use world::{Universe, Sector, Ship, Station, Planet};
+use world::{Universe, Sector, Ship, Station, Planet, Orders, Site, Market, Goods};
+use constants::{FUEL_CAPACITY, LAYOVER_TICKS, START_TIME};
+
+const RESIDENT_SPOTS: [(i32, i32); 2] = [(10, 10), (8, 14)];
 
 pub fn empty(data: &mut WorldData) {
     data.level.clear();
@@ -17,12 +26,10 @@ pub fn empty(data: &mut WorldData) {
     println!("game loaded: static empty");
 }
 
-pub fn static_station_outpost(data: &mut WorldData) {
+pub fn static_station_outpost(data: &mut WorldData, station_id: &str) {
     data.level.clear();
     data.level.backdrop = String::from("");
-    data.level.location = Location::Station("Mun".to_string());
-
-    data.universe = default_universe();
+    data.level.location = Location::Station(station_id.to_string());
 
     super::insert_floor(data, vec![
         (7, 9, FloorType::Regular),
@@ -126,23 +133,60 @@ pub fn static_station_outpost(data: &mut WorldData) {
 
     super::insert_doors(data, vec![
         (11, 14, DoorStatus::Closed, DoorType::Passage, Direction::Left),
-        (6, 13, DoorStatus::Closed, DoorType::Exit(Location::Ship("Tech 2.1".to_string())), Direction::Right)
+        (6, 13, DoorStatus::Closed, DoorType::Exit(Location::Ship(data.universe.player_ship.clone())), Direction::Right)
     ]);
 
     data.level.terminals.insert(Position::new(14, 14), Terminal {
         variant: TerminalType::Intercomm,
         dialog: Node::new(),
+        script: Some("outpost_intercomm".to_string()),
         front: Direction::Left
     });
 
+    data.level.spots.push(("counter".to_string(), Position::new(9, 10)));
+    data.level.spots.push(("quarters".to_string(), Position::new(7, 15)));
+
+    let faction = data.universe.station(station_id).map(|station| station.faction.clone()).unwrap_or_else(|| "Unclaimed".to_string());
+
     super::insert_storage(data, vec![
         (7, 9, Direction::Right),
         (7, 10, Direction::Right)
     ]);
+    for position in [Position::new(7, 9), Position::new(7, 10)].iter() {
+        if let Some(storage) = data.level.storages.get_mut(*position) {
+            storage.owner = Some(faction.clone());
+            storage.content.add(Item::PowerConductor, 1);
+        }
+    }
 
-    super::insert_npc(data, 10 ,13, guard(Direction::Left));
-
-    super::insert_npc(data, 9 ,9, gnoerf(Direction::Down));
+    let mut station_guard = guard(Direction::Left);
+    station_guard.faction = faction.clone();
+    station_guard.routine = Routine::Patrol(vec![Position::new(10, 13), Position::new(8, 15), Position::new(8, 11)]);
+    station_guard.script = Some("outpost_guard".to_string());
+    station_guard.post = Some(Post {
+        door: Position::new(11, 14),
+        inside: Direction::Right,
+        passes: vec![Condition::HasItem(Item::Permit), Condition::Reputation(faction.clone(), 5)],
+        refusal: "Union business only, show me a permit".to_string()
+    });
+    super::insert_npc(data, 10 ,13, station_guard);
+
+    let mut trader = gnoerf(Direction::Down);
+    trader.faction = faction.clone();
+    trader.routine = Routine::Schedule(vec![(8, "counter".to_string()), (20, "quarters".to_string())]);
+    trader.script = Some("outpost_trader".to_string());
+    trader.stock.sells.push((Item::Permit, 1));
+    super::insert_npc(data, 9 ,9, trader);
+
+    // the rest of the crew differs from station to station
+    let mut rng = seeded_rng(location_seed(data.universe.seed, station_id));
+    let roles = [Role::Trader, Role::Mechanic, Role::Guard];
+    for &(x, y) in RESIDENT_SPOTS.iter().take(rng.gen_range(1, RESIDENT_SPOTS.len() + 1)) {
+        let role = rng.choose(&roles).unwrap().clone();
+        let mut resident = generated_npc(&mut rng, &role, station_id, &faction, Direction::Down);
+        resident.routine = Routine::Wander(Position::new(x, y), 1);
+        super::insert_npc(data, x, y, resident);
+    }
 
     super::insert_player(data, (7, 13), Direction::Right, vec![Item::Navcomp]);
 
@@ -151,14 +195,12 @@ pub fn static_station_outpost(data: &mut WorldData) {
     println!("game loaded: static station outpost");
 }
 
-pub fn static_ship_tech(data: &mut WorldData) {
+pub fn static_ship_tech(data: &mut WorldData, ship_id: &str) {
     data.level.clear();
 
-    data.level.backdrop = String::from("/realm_of_sol__0000s_0001_2.1.png");
-    data.level.location = Location::Ship("Tech 2.1".to_string());
+    data.level.location = Location::Ship(ship_id.to_string());
+    data.level.backdrop = data.universe.backdrop(&data.level.location);
 
-    data.universe = default_universe();
-    
     super::insert_floor(data, vec![
         (7, 9, FloorType::Light),
         (8, 9, FloorType::Light),
@@ -220,6 +262,8 @@ pub fn static_ship_tech(data: &mut WorldData) {
         (10, 13, DoorStatus::Closed, DoorType::Exit(Location::Station("Mun".to_string())), Direction::Left)
     ]);
 
+    data.level.update_exits(&data.universe);
+
     super::insert_generator(data, vec![
         (8, 15, Direction::Down)
     ]);
@@ -258,7 +302,57 @@ pub fn static_ship_tech(data: &mut WorldData) {
             value: DialogItem {
                 text: "Navigate".to_string(),
                 response: "".to_string(),
-                action: Some(DialogAction::Map(MapFeature::Navigate))
+                action: Some(DialogAction::Map(MapFeature::Navigate)),
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        }
+    );
+    ship_console_dialog_children.insert(
+        Node {
+            value: DialogItem {
+                text: "Scan".to_string(),
+                response: "".to_string(),
+                action: Some(DialogAction::Scan),
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        }
+    );
+    ship_console_dialog_children.insert(
+        Node {
+            value: DialogItem {
+                text: "Dock".to_string(),
+                response: "".to_string(),
+                action: Some(DialogAction::Dock),
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        }
+    );
+    ship_console_dialog_children.insert(
+        Node {
+            value: DialogItem {
+                text: "Land".to_string(),
+                response: "".to_string(),
+                action: Some(DialogAction::Land),
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: SelectionStorage::new()
+        }
+    );
+    ship_console_dialog_children.insert(
+        Node {
+            value: DialogItem {
+                text: "Undock".to_string(),
+                response: "".to_string(),
+                action: Some(DialogAction::Undock),
+                conditions: vec![],
+                effects: vec![]
             },
             children: SelectionStorage::new()
         }
@@ -270,16 +364,20 @@ pub fn static_ship_tech(data: &mut WorldData) {
             value: DialogItem {
                 text: "".to_string(),
                 response: "ship console".to_string(),
-                action: None
+                action: None,
+                conditions: vec![],
+                effects: vec![]
             },
             children: ship_console_dialog_children
         },
+        script: None,
         front: Direction::Down
     });
 
     data.level.terminals.insert(Position::new(10, 15), Terminal {
         variant: TerminalType::Intercomm,
         dialog: Node::new(),
+        script: Some("ship_intercomm".to_string()),
         front: Direction::Left
     });
 
@@ -298,21 +396,24 @@ pub fn default_universe() -> Universe {
                 position: Position {
                     x: -3,
                     y: -2
-                }
+                },
+                hazardous: false
             },
             Sector {
                 id: "Andromeda".to_string(),
                 position: Position {
                     x: 4,
                     y: 4
-                }
+                },
+                hazardous: true
             },
             Sector {
                 id: "Gaia".to_string(),
                 position: Position {
                     x: 11,
                     y: 6
-                }
+                },
+                hazardous: false
             }
         ],
         stations: vec![
@@ -321,6 +422,27 @@ pub fn default_universe() -> Universe {
                 position: Position {
                     x: -3,
                     y: -2
+                },
+                faction: "Sol Union".to_string(),
+                market: Market {
+                    goods: vec![
+                        Goods { item: Item::PowerConductor, supply: 3, demand: 0 },
+                        Goods { item: Item::Navcomp, supply: 0, demand: 2 }
+                    ]
+                }
+            },
+            Station {
+                id: "Gaia Relay".to_string(),
+                position: Position {
+                    x: 11,
+                    y: 6
+                },
+                faction: "Free Traders".to_string(),
+                market: Market {
+                    goods: vec![
+                        Goods { item: Item::PowerConductor, supply: 0, demand: 4 },
+                        Goods { item: Item::Navcomp, supply: 2, demand: 0 }
+                    ]
                 }
             }
         ],
@@ -337,12 +459,70 @@ pub fn default_universe() -> Universe {
             Ship {
                 id: "Tech 2.1".to_string(),
                 position: Position {
-                    x: -2,
+                    x: -3,
                     y: -2
                 },
-                docked: Location::Space
+                docked: Location::Station("Mun".to_string()),
+                faction: "Independent".to_string(),
+                target: None,
+                route: vec![],
+                fuel: FUEL_CAPACITY,
+                orders: Orders::Idle,
+                layover: 0,
+                crew: vec![],
+                pilot_memory: Memory::new()
+            },
+            Ship {
+                id: "Kestrel".to_string(),
+                position: Position {
+                    x: 11,
+                    y: 6
+                },
+                docked: Location::Station("Gaia Relay".to_string()),
+                faction: "Free Traders".to_string(),
+                target: None,
+                route: vec![],
+                fuel: FUEL_CAPACITY,
+                orders: Orders::Trade,
+                layover: LAYOVER_TICKS,
+                crew: vec![],
+                pilot_memory: Memory::new()
+            },
+            Ship {
+                id: "Sol Patrol".to_string(),
+                position: Position {
+                    x: -3,
+                    y: -2
+                },
+                docked: Location::Station("Mun".to_string()),
+                faction: "Sol Union".to_string(),
+                target: None,
+                route: vec![],
+                fuel: FUEL_CAPACITY,
+                orders: Orders::Patrol(vec!["Mun".to_string(), "Gaia Relay".to_string()]),
+                layover: LAYOVER_TICKS,
+                crew: vec![],
+                pilot_memory: Memory::new()
+            }
+        ],
+        player_location: Location::Ship("Tech 2.1".to_string()),
+        player_ship: "Tech 2.1".to_string(),
+        seed: 0,
+        discovered: vec![
+            Position {
+                x: -3,
+                y: -2
+            }
+        ],
+        time: Duration::from_secs(START_TIME),
+        sites: vec![
+            Site {
+                location: Location::Ship("Tech 2.1".to_string()),
+                level: None,
+                backdrop: Some("/tech-2-1.png".to_string())
             }
         ],
-        player_location: Location::Ship("Tech 2.1".to_string())
+        flags: vec![],
+        reputation: vec![]
     }
 }
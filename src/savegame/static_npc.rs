@@ -3,6 +3,9 @@ use storage::{SelectionStorage, Node};
 use dialog::*;
 use misc::Direction;
 
+const GNOERF_CREDITS: i32 = 250;
+const PILOT_CREDITS: i32 = 100;
+
 pub fn gnoerf(direction: Direction) -> Npc {
     let look_at = direction;
     let inventory = SelectionStorage::new();
@@ -13,7 +16,9 @@ pub fn gnoerf(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Trade".to_string(),
             response: "Here are my goods".to_string(),
-            action: Some(DialogAction::Trade)
+            action: Some(DialogAction::Trade),
+            conditions: vec![],
+            effects: vec![]
         },
         children: SelectionStorage::new()
     });
@@ -21,7 +26,9 @@ pub fn gnoerf(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Bye".to_string(),
             response: "Goodbye".to_string(),
-            action: None
+            action: None,
+            conditions: vec![],
+            effects: vec![]
         },
         children: SelectionStorage::new()
     });
@@ -29,7 +36,9 @@ pub fn gnoerf(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Hi".to_string(),
             response: "Hello".to_string(),
-            action: None
+            action: None,
+            conditions: vec![],
+            effects: vec![]
         },
         children: dialog2
     });
@@ -37,7 +46,9 @@ pub fn gnoerf(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Bye".to_string(),
             response: "Goodbye".to_string(),
-            action: None
+            action: None,
+            conditions: vec![],
+            effects: vec![]
         },
         children: SelectionStorage::new()
     });
@@ -51,11 +62,23 @@ pub fn gnoerf(direction: Direction) -> Npc {
             value: DialogItem {
                 text: "".to_string(),
                 response: "...".to_string(),
-                action: None
+                action: None,
+                conditions: vec![],
+                effects: vec![]
             },
             children: dialog
         },
-        inventory
+        script: Some("gnoerf".to_string()),
+        inventory,
+        credits: GNOERF_CREDITS,
+        routine: Routine::Idle,
+        waypoint: 0,
+        ship: None,
+        faction: "Unclaimed".to_string(),
+        memory: Memory::new(),
+        stock: Stock::new(vec![(Item::PowerConductor, 3), (Item::Navcomp, 1)], vec![Item::PowerConductor, Item::Navcomp]),
+        post: None,
+        barks: vec![]
     }
 }
 
@@ -68,7 +91,9 @@ pub fn guard(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Hi".to_string(),
             response: "Hello".to_string(),
-            action: None
+            action: None,
+            conditions: vec![],
+            effects: vec![]
         },
         children: SelectionStorage::new()
     });
@@ -76,7 +101,9 @@ pub fn guard(direction: Direction) -> Npc {
         value: DialogItem {
             text: "Bye".to_string(),
             response: "Goodbye".to_string(),
-            action: None
+            action: None,
+            conditions: vec![],
+            effects: vec![]
         },
         children: SelectionStorage::new()
     });
@@ -90,10 +117,90 @@ pub fn guard(direction: Direction) -> Npc {
             value: DialogItem {
                 text: "".to_string(),
                 response: "...".to_string(),
-                action: None
+                action: None,
+                conditions: vec![],
+                effects: vec![]
             },
             children: dialog
         },
-        inventory
+        script: Some("guard".to_string()),
+        inventory,
+        credits: 0,
+        routine: Routine::Idle,
+        waypoint: 0,
+        ship: None,
+        faction: "Unclaimed".to_string(),
+        memory: Memory::new(),
+        stock: Stock::new(vec![], vec![]),
+        post: None,
+        barks: vec![]
     }
-}
\ No newline at end of file
+}
+
+pub fn pilot(direction: Direction, ship_id: &str, faction: &str) -> Npc {
+    let look_at = direction;
+    let inventory = SelectionStorage::new();
+
+    let mut dialog = SelectionStorage::new();
+    dialog.insert(Node {
+        value: DialogItem {
+            text: "Hi".to_string(),
+            response: format!("Just docked with the {}, we fly for {}", ship_id, faction),
+            action: None,
+            conditions: vec![],
+            effects: vec![]
+        },
+        children: SelectionStorage::new()
+    });
+    dialog.insert(Node {
+        value: DialogItem {
+            text: "Trade".to_string(),
+            response: "Have a look".to_string(),
+            action: Some(DialogAction::Trade),
+            conditions: vec![],
+            effects: vec![]
+        },
+        children: SelectionStorage::new()
+    });
+    dialog.insert(Node {
+        value: DialogItem {
+            text: "Bye".to_string(),
+            response: "Fly safe".to_string(),
+            action: None,
+            conditions: vec![],
+            effects: vec![]
+        },
+        children: SelectionStorage::new()
+    });
+
+    Npc {
+        name: format!("Pilot of {}", ship_id),
+        variant: NpcType::Gnoerf,
+        direction,
+        look_at,
+        dialog: Node {
+            value: DialogItem {
+                text: "".to_string(),
+                response: "...".to_string(),
+                action: None,
+                conditions: vec![],
+                effects: vec![]
+            },
+            children: dialog
+        },
+        script: None,
+        inventory,
+        credits: PILOT_CREDITS,
+        routine: Routine::Idle,
+        waypoint: 0,
+        ship: Some(ship_id.to_string()),
+        faction: faction.to_string(),
+        memory: Memory::new(),
+        stock: Stock::new(vec![(Item::PowerConductor, 2)], vec![Item::Navcomp]),
+        post: None,
+        barks: vec![
+            Bark { text: format!("The {} needs a navcomp, got one?", ship_id), conditions: vec![Condition::HasItem(Item::Navcomp)] },
+            Bark { text: "Can not wait to undock".to_string(), conditions: vec![] }
+        ]
+    }
+}
@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+use objects::{Location, Item, Memory};
+use misc::Position;
+use world::{Universe, Sector, Ship, Station, Planet, Orders, Site, Market};
+use constants::{FUEL_CAPACITY, START_TIME};
+
+pub const UNIVERSE_FILE: &str = "levels/universe.txt";
+
+// the universe file is read at every new game so locations can be added without a recompile,
+// the static universe is the fallback when the file is missing or broken
+pub fn load_universe() -> Universe {
+    match read_universe(UNIVERSE_FILE) {
+        Ok(universe) => {
+            println!("universe loaded from file: {}", UNIVERSE_FILE);
+            universe
+        },
+        Err(error) => {
+            println!("universe file not used: {}", error);
+            super::static_levels::default_universe()
+        }
+    }
+}
+
+pub fn read_universe(filename: &str) -> Result<Universe, String> {
+    let mut content = String::new();
+    File::open(filename)
+        .and_then(|mut file| file.read_to_string(&mut content))
+        .map_err(|error| format!("{}: {}", filename, error))?;
+
+    parse_universe(&content)
+}
+
+// one location per line: kind | id | x | y | attributes
+// attributes are flags (hazardous, player) or key=value pairs (faction, supply, demand, docked, orders, fuel, level, backdrop)
+pub fn parse_universe(content: &str) -> Result<Universe, String> {
+    let mut universe = Universe {
+        sectors: vec![],
+        stations: vec![],
+        ships: vec![],
+        planets: vec![],
+        player_location: Location::Space,
+        player_ship: String::new(),
+        seed: 0,
+        discovered: vec![],
+        time: Duration::from_secs(START_TIME),
+        sites: vec![],
+        flags: vec![],
+        reputation: vec![]
+    };
+    let mut docking: Vec<(String, String)> = vec![];
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').map(|field| field.trim()).collect();
+        if fields.len() < 4 {
+            return Err(format!("line {}: expected kind | id | x | y", index + 1));
+        }
+        let id = fields[1].to_string();
+        let position = Position::new(
+            fields[2].parse().map_err(|_| format!("line {}: invalid x '{}'", index + 1, fields[2]))?,
+            fields[3].parse().map_err(|_| format!("line {}: invalid y '{}'", index + 1, fields[3]))?
+        );
+
+        let mut flags = vec![];
+        let mut attributes = vec![];
+        for field in fields[4..].iter() {
+            match field.find('=') {
+                Some(split) => attributes.push((&field[..split], field[split + 1..].trim())),
+                None => flags.push(*field)
+            }
+        }
+        let attribute = |key: &str| attributes.iter().find(|&&(name, _)| name == key).map(|&(_, value)| value.to_string());
+        let faction = attribute("faction").unwrap_or_else(|| "Unclaimed".to_string());
+
+        let location = match fields[0] {
+            "sector" => {
+                universe.sectors.push(Sector {
+                    id,
+                    position,
+                    hazardous: flags.contains(&"hazardous")
+                });
+                None
+            },
+            "station" => {
+                let mut market = Market::new();
+                for (key, value) in attributes.iter().filter(|&&(key, _)| key == "supply" || key == "demand") {
+                    for entry in value.split(',') {
+                        let (item, amount) = parse_goods(entry).ok_or_else(|| format!("line {}: invalid goods '{}'", index + 1, entry))?;
+                        let goods = market.goods_mut(&item);
+                        if *key == "supply" {
+                            goods.supply = amount;
+                        } else {
+                            goods.demand = amount;
+                        }
+                    }
+                }
+                universe.stations.push(Station {
+                    id: id.clone(),
+                    position,
+                    faction,
+                    market
+                });
+                Some(Location::Station(id))
+            },
+            "planet" => {
+                universe.planets.push(Planet {
+                    id: id.clone(),
+                    position
+                });
+                Some(Location::Planet(id))
+            },
+            "ship" => {
+                let orders = match attribute("orders") {
+                    Some(ref orders) if orders == "trade" => Orders::Trade,
+                    Some(ref orders) if orders.starts_with("patrol:") => {
+                        Orders::Patrol(orders["patrol:".len()..].split(',').map(|station| station.trim().to_string()).collect())
+                    },
+                    Some(ref orders) if orders != "idle" => {
+                        return Err(format!("line {}: unknown orders '{}'", index + 1, orders));
+                    },
+                    _ => Orders::Idle
+                };
+                let fuel = match attribute("fuel") {
+                    Some(fuel) => fuel.parse().map_err(|_| format!("line {}: invalid fuel '{}'", index + 1, fuel))?,
+                    None => FUEL_CAPACITY
+                };
+                if let Some(docked) = attribute("docked") {
+                    docking.push((id.clone(), docked));
+                }
+                if flags.contains(&"player") {
+                    universe.player_location = Location::Ship(id.clone());
+                    universe.player_ship = id.clone();
+                    universe.discover(position);
+                }
+
+                universe.ships.push(Ship {
+                    id: id.clone(),
+                    position,
+                    docked: Location::Space,
+                    faction,
+                    target: None,
+                    route: vec![],
+                    fuel,
+                    orders,
+                    layover: 0,
+                    crew: vec![],
+                    pilot_memory: Memory::new()
+                });
+                Some(Location::Ship(id))
+            },
+            kind => {
+                return Err(format!("line {}: unknown kind '{}'", index + 1, kind));
+            }
+        };
+
+        if let Some(location) = location {
+            let level = attribute("level");
+            let backdrop = attribute("backdrop");
+            if level.is_some() || backdrop.is_some() {
+                universe.sites.push(Site {
+                    location,
+                    level,
+                    backdrop
+                });
+            }
+        }
+    }
+
+    // docking refers to stations and planets by id, they may be listed after the ship
+    for (ship_id, docked_id) in docking {
+        let docked = if universe.station(&docked_id).is_some() {
+            Location::Station(docked_id)
+        } else if universe.planet(&docked_id).is_some() {
+            Location::Planet(docked_id)
+        } else {
+            return Err(format!("{} is docked at unknown location {}", ship_id, docked_id));
+        };
+        if let Some(ship) = universe.ship_mut(&ship_id) {
+            ship.docked = docked;
+        }
+    }
+
+    if universe.player_location == Location::Space {
+        return Err("no ship is marked as the player's".to_string());
+    }
+
+    Ok(universe)
+}
+
+// goods are written as Item:amount, e.g. Navcomp:2
+fn parse_goods(entry: &str) -> Option<(Item, i32)> {
+    let mut parts = entry.split(':').map(|part| part.trim());
+    let item = parse_item(parts.next()?)?;
+    let amount = parts.next().and_then(|amount| amount.parse().ok())?;
+
+    Some((item, amount))
+}
+
+pub fn parse_item(name: &str) -> Option<Item> {
+    match name {
+        "PowerConductor" => Some(Item::PowerConductor),
+        "Navcomp" => Some(Item::Navcomp),
+        "Permit" => Some(Item::Permit),
+        _ => None
+    }
+}
@@ -1,8 +1,9 @@
-use std::cmp::max;
+use std::cmp::{max, min};
 use std::fmt::Debug;
 use std::slice;
 use dialog::DialogItem;
 use misc::Position;
+use objects::{Item, Stack};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct PositionLevelStorage<T: Clone> {
@@ -168,13 +169,88 @@ impl<T: Clone> SelectionStorage<T> {
     }
 }
 
+// items of the same kind merge into one stack, taking from a stack splits it
+impl SelectionStorage<Stack> {
+    pub fn add(&mut self, item: Item, count: usize) {
+        if count == 0 {
+            return;
+        }
+        if let Some(stack) = self.storage.iter_mut().find(|stack| stack.item == item) {
+            stack.count += count;
+            return;
+        }
+        self.storage.push(Stack::new(item, count));
+    }
+
+    pub fn add_stack(&mut self, stack: Stack) {
+        self.add(stack.item, stack.count);
+    }
+
+    pub fn count(&self, item: &Item) -> usize {
+        self.storage.iter().filter(|stack| &stack.item == item).map(|stack| stack.count).sum()
+    }
+
+    // removes the given number of an item when there are enough of it
+    pub fn take(&mut self, item: &Item, count: usize) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+
+        let mut left = count;
+        for stack in self.storage.iter_mut().filter(|stack| &stack.item == item) {
+            let taken = min(left, stack.count);
+            stack.count -= taken;
+            left -= taken;
+        }
+        self.remove_empty();
+
+        true
+    }
+
+    // splits up to the given number off the selected stack
+    pub fn take_current(&mut self, count: usize) -> Option<Stack> {
+        let taken = match self.storage.get_mut(self.current_selection) {
+            Some(stack) => {
+                let taken = min(count, stack.count);
+                stack.count -= taken;
+                Stack::new(stack.item, taken)
+            },
+            None => return None
+        };
+        self.remove_empty();
+
+        Some(taken)
+    }
+
+    // every single item, for checks that go item by item
+    pub fn items(&self) -> Vec<Item> {
+        let mut items = vec![];
+        for stack in self.storage.iter() {
+            for _ in 0..stack.count {
+                items.push(stack.item);
+            }
+        }
+
+        items
+    }
+
+    fn remove_empty(&mut self) {
+        self.storage.retain(|stack| stack.count > 0);
+        if self.storage.len() <= self.current_selection && self.current_selection > 0 {
+            self.current_selection = self.storage.len().saturating_sub(1);
+        }
+    }
+}
+
 impl Node<DialogItem> {
     pub fn new() -> Node<DialogItem> {
         Node {
             value: DialogItem {
                 text: "".to_string(),
                 response: "[ ]".to_string(),
-                action: None
+                action: None,
+                conditions: vec![],
+                effects: vec![]
             },
             children: SelectionStorage::new()
         }
@@ -1,20 +1,87 @@
 use std::time::Duration;
-use std::collections::{VecDeque, BTreeSet, HashMap};
+use std::cmp::{min, max, Reverse};
+use std::collections::{VecDeque, BTreeSet, BTreeMap, BinaryHeap, HashMap};
 
 use ggez::Context;
 use ggez::graphics::{spritebatch::SpriteBatch, Image};
+use rand::{thread_rng, Rng};
+use rand::prng::ChaChaRng;
 
 use player::*;
 use storage::*;
 use objects::*;
 use misc::*;
 use app::{SpriteId, BackdropId};
+use constants::{DISTRUST_STANDING, RESTOCK_INTERVAL, WANDER_CHANCE, VISITOR_WANDER_RADIUS, STARTING_CREDITS, LEVEL_SIZE, SENSOR_RANGE, FUEL_CAPACITY, LAYOVER_TICKS, TIME_SCALE, SECONDS_PER_DAY, JUMP_TIME, NAVIGATOR_JUMP_TIME, ENGINEER_FUEL_SAVING, CIRCUITRY_CONDUCTORS};
 use savegame;
+use savegame::generated_universe::{seeded_rng, location_seed};
+use quest::Quest;
+use bark::Bubble;
+
+const STEP_COST: i32 = 10;
+const DIAGONAL_STEP_COST: i32 = 14;
+const HAZARD_COST: i32 = 50;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Goods {
+    pub item: Item,
+    pub supply: i32,
+    pub demand: i32
+}
+
+// every unit of supply or demand moves the price of an item by a tenth of its base price
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Market {
+    pub goods: Vec<Goods>
+}
+
+impl Market {
+    pub fn new() -> Market {
+        Market {
+            goods: vec![]
+        }
+    }
+
+    // the price once the supply changed by the given amount, a trade prices every unit after its own change
+    pub fn price_at(&self, item: &Item, supply_change: i32) -> i32 {
+        let base = item.base_price();
+        let shift = self.goods.iter()
+            .find(|goods| &goods.item == item)
+            .map_or(0, |goods| goods.demand - goods.supply) - supply_change;
+
+        max(base / 4, base * (10 + shift) / 10).max(1)
+    }
+
+    // the station stocks what it buys and runs short on what it sells
+    pub fn trade(&mut self, bought: &[Item], sold: &[Item]) {
+        for item in bought {
+            self.goods_mut(item).supply += 1;
+        }
+        for item in sold {
+            self.goods_mut(item).supply -= 1;
+        }
+    }
+
+    pub fn goods_mut(&mut self, item: &Item) -> &mut Goods {
+        if let Some(index) = self.goods.iter().position(|goods| &goods.item == item) {
+            return &mut self.goods[index];
+        }
+
+        self.goods.push(Goods {
+            item: *item,
+            supply: 0,
+            demand: 0
+        });
+        self.goods.last_mut().unwrap()
+    }
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Station {
     pub id: String,
-    pub position: Position
+    pub position: Position,
+    pub faction: String,
+    pub market: Market
 }
 
 #[derive(Serialize, Deserialize)]
@@ -23,17 +90,50 @@ pub struct Planet {
     pub position: Position
 }
 
+// what a ship does on its own when nobody is at the helm
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub enum Orders {
+    Idle,
+    Trade,
+    Patrol(Vec<String>)
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Ship {
     pub id: String,
     pub position: Position,
-    pub docked: Location
+    pub docked: Location,
+    pub faction: String,
+    pub target: Option<Location>,
+    pub route: Vec<Position>,
+    pub fuel: i32,
+    pub orders: Orders,
+    pub layover: u32,
+    // the posts manned by the crew aboard, each gives the ship a bonus
+    pub crew: Vec<CrewStation>,
+    // the pilot leaves with the ship, what it remembers of the player comes back with it
+    pub pilot_memory: Memory
+}
+
+impl Ship {
+    pub fn is_docked(&self) -> bool {
+        self.docked != Location::Space
+    }
+}
+
+// the level file and backdrop image backing a location, both optional
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Site {
+    pub location: Location,
+    pub level: Option<String>,
+    pub backdrop: Option<String>
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Sector {
     pub id: String,
-    pub position: Position
+    pub position: Position,
+    pub hazardous: bool
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -50,7 +150,8 @@ pub struct Level {
     pub generators: PositionLevelStorage<Generator>,
     pub pilot_seats: PositionLevelStorage<PilotSeat>,
     pub npc: PositionLevelStorage<Npc>,
-    pub storages: PositionLevelStorage<Storage>
+    pub storages: PositionLevelStorage<Storage>,
+    pub spots: Vec<(String, Position)>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -59,10 +160,66 @@ pub struct Universe {
     pub stations: Vec<Station>,
     pub ships: Vec<Ship>,
     pub planets: Vec<Planet>,
-    pub player_location: Location
+    pub player_location: Location,
+    // the ship the player set out with, its level is built when no level file exists
+    pub player_ship: String,
+    pub seed: u64,
+    pub discovered: Vec<Position>,
+    pub time: Duration,
+    pub sites: Vec<Site>,
+    pub flags: Vec<String>,
+    pub reputation: Vec<(String, i32)>
 }
 
 impl Universe {
+    pub fn advance_time(&mut self, delta: Duration) {
+        self.time += delta * TIME_SCALE;
+    }
+
+    pub fn day(&self) -> u64 {
+        self.time.as_secs() / SECONDS_PER_DAY + 1
+    }
+
+    pub fn calendar(&self) -> String {
+        let seconds = self.time.as_secs() % SECONDS_PER_DAY;
+
+        format!("Day {}, {:02}:{:02}", self.day(), seconds / 3600, seconds / 60 % 60)
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|current| current == flag)
+    }
+
+    pub fn set_flag(&mut self, flag: &str) {
+        if !self.has_flag(flag) {
+            self.flags.push(flag.to_string());
+        }
+    }
+
+    pub fn clear_flag(&mut self, flag: &str) {
+        self.flags.retain(|current| current != flag);
+    }
+
+    pub fn reputation(&self, faction: &str) -> i32 {
+        self.reputation.iter()
+            .find(|&&(ref current, _)| current == faction)
+            .map(|&(_, value)| value)
+            .unwrap_or(0)
+    }
+
+    pub fn change_reputation(&mut self, faction: &str, amount: i32) {
+        if let Some(entry) = self.reputation.iter_mut().find(|&&mut (ref current, _)| current == faction) {
+            entry.1 += amount;
+            return;
+        }
+        self.reputation.push((faction.to_string(), amount));
+    }
+
+    // how an npc thinks of the player, its own memory plus the standing with its faction
+    pub fn standing(&self, npc: &Npc) -> i32 {
+        npc.memory.reputation + self.reputation(&npc.faction)
+    }
+
     pub fn has_stations(&self, pos: &Position) -> bool {
         let mut stations = false;
         for station in self.stations.iter() {
@@ -84,18 +241,415 @@ impl Universe {
 
         planets
     }
+
+    pub fn station_at(&self, pos: &Position) -> Option<&Station> {
+        self.stations.iter().find(|station| &station.position == pos)
+    }
+
+    pub fn stations_at(&self, pos: &Position) -> Vec<Location> {
+        self.stations.iter()
+            .filter(|station| &station.position == pos)
+            .map(|station| Location::Station(station.id.clone()))
+            .collect()
+    }
+
+    pub fn player_position(&self) -> Option<Position> {
+        match self.player_location {
+            Location::Ship(ref ship_id) => self.ship(ship_id).map(|ship| ship.position),
+            Location::Station(ref station_id) => self.station(station_id).map(|station| station.position),
+            Location::Planet(ref planet_id) => self.planet(planet_id).map(|planet| planet.position),
+            Location::Space => None
+        }
+    }
+
+    pub fn is_discovered(&self, pos: &Position) -> bool {
+        self.discovered.contains(pos)
+    }
+
+    pub fn in_sensor_range(&self, pos: &Position) -> bool {
+        if let Some(player_position) = self.player_position() {
+            player_position.dist(pos) <= SENSOR_RANGE
+        } else {
+            false
+        }
+    }
+
+    // returns false when the position was already known
+    pub fn discover(&mut self, pos: Position) -> bool {
+        if self.is_discovered(&pos) {
+            false
+        } else {
+            self.discovered.push(pos);
+            true
+        }
+    }
+
+    // discovers everything within sensor range and returns how many new positions were found
+    pub fn scan(&mut self) -> usize {
+        let mut contacts: Vec<Position> = vec![];
+        contacts.extend(self.sectors.iter().map(|sector| sector.position));
+        contacts.extend(self.stations.iter().map(|station| station.position));
+        contacts.extend(self.planets.iter().map(|planet| planet.position));
+        contacts.extend(self.ships.iter().map(|ship| ship.position));
+
+        let mut found = 0;
+        for pos in contacts {
+            if self.in_sensor_range(&pos) && self.discover(pos) {
+                found += 1;
+            }
+        }
+
+        found
+    }
+
+    pub fn site(&self, location: &Location) -> Option<&Site> {
+        self.sites.iter().find(|site| &site.location == location)
+    }
+
+    pub fn backdrop(&self, location: &Location) -> String {
+        self.site(location).and_then(|site| site.backdrop.clone()).unwrap_or_default()
+    }
+
+    pub fn planets_at(&self, pos: &Position) -> Vec<Location> {
+        self.planets.iter()
+            .filter(|planet| &planet.position == pos)
+            .map(|planet| Location::Planet(planet.id.clone()))
+            .collect()
+    }
+
+    pub fn planet(&self, planet_id: &str) -> Option<&Planet> {
+        self.planets.iter().find(|planet| planet.id == planet_id)
+    }
+
+    pub fn station(&self, station_id: &str) -> Option<&Station> {
+        self.stations.iter().find(|station| station.id == station_id)
+    }
+
+    pub fn market(&self, location: &Location) -> Option<&Market> {
+        match location {
+            Location::Station(id) => self.station(id).map(|station| &station.market),
+            _ => None
+        }
+    }
+
+    pub fn market_mut(&mut self, location: &Location) -> Option<&mut Market> {
+        match location {
+            Location::Station(id) => self.stations.iter_mut().find(|station| &station.id == id).map(|station| &mut station.market),
+            _ => None
+        }
+    }
+
+    pub fn ship(&self, ship_id: &str) -> Option<&Ship> {
+        self.ships.iter().find(|ship| ship.id == ship_id)
+    }
+
+    pub fn ship_mut(&mut self, ship_id: &str) -> Option<&mut Ship> {
+        self.ships.iter_mut().find(|ship| ship.id == ship_id)
+    }
+
+    // the navigation target when several candidates share the ship's position, otherwise the only one there
+    fn pick_target(ship: &Ship, candidates: Vec<Location>) -> Option<Location> {
+        if let Some(ref target) = ship.target {
+            if candidates.contains(target) {
+                return Some(target.clone());
+            }
+        }
+
+        if candidates.len() == 1 {
+            candidates.into_iter().next()
+        } else {
+            None
+        }
+    }
+
+    pub fn dock(&mut self, ship_id: &str) -> Option<Location> {
+        let station_location = self.ship(ship_id)
+            .and_then(|ship| Universe::pick_target(ship, self.stations_at(&ship.position)));
+
+        if let Some(ref location) = station_location {
+            if let Some(ship) = self.ship_mut(ship_id) {
+                ship.docked = location.clone();
+                ship.fuel = FUEL_CAPACITY;
+            }
+        }
+
+        station_location
+    }
+
+    // landing on a planet is docking at it, the ship's exits lead onto the surface
+    pub fn land(&mut self, ship_id: &str) -> Option<Location> {
+        let planet_location = self.ship(ship_id)
+            .and_then(|ship| Universe::pick_target(ship, self.planets_at(&ship.position)));
+
+        if let Some(ref location) = planet_location {
+            if let Some(ship) = self.ship_mut(ship_id) {
+                ship.docked = location.clone();
+            }
+        }
+
+        planet_location
+    }
+
+    pub fn undock(&mut self, ship_id: &str) -> bool {
+        if let Some(ship) = self.ship_mut(ship_id) {
+            if ship.is_docked() {
+                ship.docked = Location::Space;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn hail(&self, location: &Location) -> String {
+        match location {
+            Location::Station(id) => {
+                match self.station(id) {
+                    Some(station) => format!("This is {} control, {}. You are cleared to dock.", station.id, station.faction),
+                    None => "No response".to_string()
+                }
+            },
+            Location::Ship(id) => {
+                match self.ship(id) {
+                    Some(_) if self.player_location == *location => "That is your own ship".to_string(),
+                    Some(ship) if ship.faction == "Unclaimed" => "Only static answers the hail".to_string(),
+                    Some(ship) => format!("{} of the {} acknowledges your hail", ship.id, ship.faction),
+                    None => "No response".to_string()
+                }
+            },
+            Location::Planet(id) => format!("There is no transmitter on {}", id),
+            Location::Space => "Nobody is out there".to_string()
+        }
+    }
+
+    // moving a ship always leaves the station it was docked at
+    pub fn move_ship(&mut self, ship_id: &str, position: Position) {
+        if let Some(ship) = self.ship_mut(ship_id) {
+            ship.position = position;
+            ship.docked = Location::Space;
+        }
+    }
+
+    // docks or lands the ship at its navigation target once it shares its position
+    pub fn arrive(&mut self, ship_id: &str) -> Option<Location> {
+        let mut target = None;
+        if let Some(ship) = self.ship(ship_id) {
+            if let Some(ref location) = ship.target {
+                let reached = match location {
+                    Location::Station(id) => self.station(id).map(|station| station.position == ship.position),
+                    Location::Planet(id) => self.planet(id).map(|planet| planet.position == ship.position),
+                    _ => None
+                };
+                if reached == Some(true) {
+                    target = Some(location.clone());
+                }
+            }
+        }
+
+        if let Some(ref location) = target {
+            if let Some(ship) = self.ship_mut(ship_id) {
+                if let Location::Station(_) = location {
+                    ship.fuel = FUEL_CAPACITY;
+                    ship.layover = LAYOVER_TICKS;
+                }
+                ship.docked = location.clone();
+                ship.target = None;
+            }
+        }
+
+        target
+    }
+
+    pub fn is_hazardous(&self, pos: &Position) -> bool {
+        self.sectors.iter().any(|sector| sector.hazardous && sector.position.dist(pos) < 1.5)
+    }
+
+    // A* across the universe grid, the path excludes the start and may not be longer than the fuel left
+    // a tile reached in fewer steps is a different state, a cheaper detour may run out of fuel where it does not
+    pub fn plan_route(&self, from: Position, to: Position, fuel: i32) -> Option<Vec<Position>> {
+        let mut open = BinaryHeap::new();
+        let mut costs: BTreeMap<(Position, i32), i32> = BTreeMap::new();
+        let mut came_from: BTreeMap<(Position, i32), (Position, i32)> = BTreeMap::new();
+
+        costs.insert((from, 0), 0);
+        open.push(Reverse((route_heuristic(from, to), from, 0)));
+
+        while let Some(Reverse((_, current, steps))) = open.pop() {
+            if current == to {
+                let mut path = vec![];
+                let mut state = (current, steps);
+                while state != (from, 0) {
+                    path.push(state.0);
+                    state = came_from[&state];
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            let cost = costs[&(current, steps)];
+            if steps >= fuel {
+                continue;
+            }
+
+            for &(x, y) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)].iter() {
+                let neighbor = Position::new(current.x + x, current.y + y);
+                let mut next_cost = cost + if x != 0 && y != 0 { DIAGONAL_STEP_COST } else { STEP_COST };
+                if self.is_hazardous(&neighbor) {
+                    next_cost += HAZARD_COST;
+                }
+
+                let state = (neighbor, steps + 1);
+                let better = match costs.get(&state) {
+                    Some(&known_cost) => next_cost < known_cost,
+                    None => true
+                };
+                if better {
+                    costs.insert(state, next_cost);
+                    came_from.insert(state, (current, steps));
+                    open.push(Reverse((next_cost + route_heuristic(neighbor, to), neighbor, steps + 1)));
+                }
+            }
+        }
+
+        None
+    }
+
+    // gives ships without a pilot aboard their next destination, uses the same routes and fuel as the player
+    // returns true when a ship hopped to a station in the same sector right away
+    pub fn simulate(&mut self) -> bool {
+        let mut plans = vec![];
+        let mut docked = false;
+        for ship in self.ships.iter() {
+            let mut rng = traffic_rng(self.seed, &self.time, &ship.id);
+            if self.player_location == Location::Ship(ship.id.clone()) || ship.orders == Orders::Idle || !ship.route.is_empty() {
+                continue;
+            }
+            if ship.layover > 0 {
+                plans.push((ship.id.clone(), None));
+                continue;
+            }
+
+            let destination = match ship.orders {
+                Orders::Trade => {
+                    let markets: Vec<&Station> = self.stations.iter()
+                        .filter(|station| ship.docked != Location::Station(station.id.clone()))
+                        .collect();
+                    rng.choose(&markets).map(|station| station.id.clone())
+                },
+                Orders::Patrol(ref waypoints) => {
+                    let next = waypoints.iter()
+                        .position(|id| ship.docked == Location::Station(id.clone()))
+                        .map_or(0, |index| (index + 1) % waypoints.len());
+                    waypoints.get(next).cloned()
+                },
+                Orders::Idle => None
+            };
+
+            if let Some(station) = destination.and_then(|id| self.station(&id)) {
+                if let Some(route) = self.plan_route(ship.position, station.position, ship.fuel) {
+                    plans.push((ship.id.clone(), Some((route, Location::Station(station.id.clone())))));
+                }
+            }
+        }
+
+        for (ship_id, plan) in plans {
+            match plan {
+                Some((route, target)) => {
+                    let arrived = route.is_empty();
+                    if let Some(ship) = self.ship_mut(&ship_id) {
+                        ship.route = route;
+                        ship.target = Some(target);
+                    }
+                    if arrived {
+                        self.undock(&ship_id);
+                        self.arrive(&ship_id);
+                        docked = true;
+                    }
+                },
+                None => {
+                    if let Some(ship) = self.ship_mut(&ship_id) {
+                        ship.layover -= 1;
+                    }
+                }
+            }
+        }
+
+        docked
+    }
+
+    // advances every ship one step along its route, returns true when any ship moved
+    pub fn travel(&mut self) -> bool {
+        let mut moved = vec![];
+        for ship in self.ships.iter_mut() {
+            if !ship.route.is_empty() && ship.fuel > 0 {
+                let mut rng = traffic_rng(self.seed, &self.time, &ship.id);
+                let position = ship.route.remove(0);
+                // someone at the generator gets more out of the fuel, someone at the console plots faster jumps
+                if !(ship.crew.contains(&CrewStation::Generator) && rng.gen_bool(ENGINEER_FUEL_SAVING)) {
+                    ship.fuel -= 1;
+                }
+                let jump_time = if ship.crew.contains(&CrewStation::Console) { NAVIGATOR_JUMP_TIME } else { JUMP_TIME };
+                moved.push((ship.id.clone(), position, ship.route.is_empty(), jump_time));
+            }
+        }
+
+        for &(ref ship_id, position, route_finished, jump_time) in moved.iter() {
+            self.move_ship(ship_id, position);
+            // time flies while the player is aboard a travelling ship
+            if self.player_location == Location::Ship(ship_id.clone()) {
+                self.discover(position);
+                self.time += Duration::from_secs(jump_time);
+            }
+            if route_finished {
+                self.arrive(ship_id);
+            }
+        }
+
+        !moved.is_empty()
+    }
+
+    // ships that ran out of fuel with jumps left drop their route and drift where they are
+    pub fn strand(&mut self) -> Vec<String> {
+        let mut stranded = vec![];
+        for ship in self.ships.iter_mut() {
+            if ship.fuel <= 0 && !ship.route.is_empty() {
+                ship.route.clear();
+                ship.target = None;
+                stranded.push(ship.id.clone());
+            }
+        }
+
+        stranded
+    }
+}
+
+// ships draw from the universe seed so the same universe plays out the same traffic
+fn traffic_rng(seed: u64, time: &Duration, ship_id: &str) -> ChaChaRng {
+    seeded_rng(location_seed(seed, ship_id) ^ time.as_secs())
+}
+
+fn route_heuristic(from: Position, to: Position) -> i32 {
+    let dx = (to.x - from.x).abs();
+    let dy = (to.y - from.y).abs();
+
+    STEP_COST * (dx + dy) + (DIAGONAL_STEP_COST - 2 * STEP_COST) * min(dx, dy)
 }
 
 pub struct WorldData {
     pub movement_timer: Duration,
+    pub travel_timer: Duration,
+    pub npc_timer: Duration,
     pub level: Level,
     pub universe: Universe,
     pub receipes: Vec<Receipe>,
+    pub quests: Vec<Quest>,
     pub insight_view: bool,
     pub overlay: bool,
     pub sprites: HashMap<SpriteId, SpriteBatch>,
     pub backdrops: HashMap<BackdropId, Image>,
     pub levels: HashMap<Location, Level>,
+    pub bubbles: Vec<Bubble>,
     pub camera: Position
 }
 
@@ -111,12 +665,15 @@ impl WorldData {
             direction: player_direction,
             front_tile: player_front_tile,
             inventory,
+            credits: STARTING_CREDITS,
             terminal: Box::new(Terminal {
                 variant: TerminalType::Intercomm,
                 dialog: Node::new(),
+                script: None,
                 front: Direction::Down
             }),
-            log: SelectionStorage::new()
+            log: SelectionStorage::new(),
+            quests: vec![]
         };
 
         let walls = <PositionLevelStorage<Wall>>::new();
@@ -136,7 +693,6 @@ impl WorldData {
         backdrops.insert(BackdropId::MapSector, Image::new(ctx, "/window-backdrop-sector.png").unwrap());
         backdrops.insert(BackdropId::MapPlanet, Image::new(ctx, "/window-backdrop-planet.png").unwrap());
         backdrops.insert(BackdropId::MapStation, Image::new(ctx, "/window-backdrop-station.png").unwrap());
-        backdrops.insert(BackdropId::Location(Location::Ship("Tech 2.1".to_string())), Image::new(ctx, "/tech-2-1.png").unwrap());
 
         let mut sprites = HashMap::new();
         sprites.insert(SpriteId::Wall, SpriteBatch::new(Image::new(ctx, "/wall.png").unwrap()));
@@ -145,10 +701,15 @@ impl WorldData {
         sprites.insert(SpriteId::Window, SpriteBatch::new(Image::new(ctx, "/window.png").unwrap()));
         sprites.insert(SpriteId::Floor(FloorType::Regular), SpriteBatch::new(Image::new(ctx, "/floor.png").unwrap()));
         sprites.insert(SpriteId::Floor(FloorType::Light), SpriteBatch::new(Image::new(ctx, "/floor-light.png").unwrap()));
+        sprites.insert(SpriteId::Floor(FloorType::Soil), SpriteBatch::new(Image::new(ctx, "/soil.png").unwrap()));
+        sprites.insert(SpriteId::Floor(FloorType::Gravel), SpriteBatch::new(Image::new(ctx, "/gravel.png").unwrap()));
+        sprites.insert(SpriteId::Floor(FloorType::LandingPad), SpriteBatch::new(Image::new(ctx, "/landing-pad.png").unwrap()));
+        sprites.insert(SpriteId::Rock, SpriteBatch::new(Image::new(ctx, "/rock.png").unwrap()));
         sprites.insert(SpriteId::Circuitry(CircuitryType::Powered), SpriteBatch::new(Image::new(ctx, "/circuitry.png").unwrap()));
         sprites.insert(SpriteId::Circuitry(CircuitryType::Inactive), SpriteBatch::new(Image::new(ctx, "/circuitry-inactive.png").unwrap()));
         sprites.insert(SpriteId::Door(DoorStatus::Closed), SpriteBatch::new(Image::new(ctx, "/door.png").unwrap()));
         sprites.insert(SpriteId::Door(DoorStatus::Open), SpriteBatch::new(Image::new(ctx, "/door-open.png").unwrap()));
+        sprites.insert(SpriteId::Door(DoorStatus::Locked), SpriteBatch::new(Image::new(ctx, "/door.png").unwrap()));
         sprites.insert(SpriteId::Terminal(TerminalType::Intercomm), SpriteBatch::new(Image::new(ctx, "/terminal.png").unwrap()));
         sprites.insert(SpriteId::Terminal(TerminalType::ShipConsole), SpriteBatch::new(Image::new(ctx, "/ship-console.png").unwrap()));
         sprites.insert(SpriteId::PilotSeat, SpriteBatch::new(Image::new(ctx, "/pilot-seat.png").unwrap()));
@@ -157,11 +718,14 @@ impl WorldData {
         sprites.insert(SpriteId::MapSector, SpriteBatch::new(Image::new(ctx, "/map-sector.png").unwrap()));
         sprites.insert(SpriteId::MapStation, SpriteBatch::new(Image::new(ctx, "/map-station.png").unwrap()));
         sprites.insert(SpriteId::MapShip, SpriteBatch::new(Image::new(ctx, "/map-ship.png").unwrap()));
+        sprites.insert(SpriteId::MapUnknown, SpriteBatch::new(Image::new(ctx, "/map-unknown.png").unwrap()));
         sprites.insert(SpriteId::Decoration(DecorationType::Display), SpriteBatch::new(Image::new(ctx, "/display.png").unwrap()));
         sprites.insert(SpriteId::Decoration(DecorationType::Panel), SpriteBatch::new(Image::new(ctx, "/panel.png").unwrap()));
 
-        WorldData {
+        let mut world = WorldData {
             movement_timer: Duration::from_millis(0),
+            travel_timer: Duration::from_millis(0),
+            npc_timer: Duration::from_millis(0),
             level: Level {
                 backdrop: String::from(""),
                 location: Location::Space,
@@ -175,16 +739,41 @@ impl WorldData {
                 generators,
                 pilot_seats,
                 npc,
-                storages
+                storages,
+                spots: vec![]
             },
-            universe: savegame::static_levels::default_universe(),
+            universe: savegame::universe_file::load_universe(),
             receipes,
+            quests: savegame::quest_file::load_quests(),
             insight_view: false,
             overlay: false,
             sprites,
             backdrops,
             levels: HashMap::new(),
+            bubbles: vec![],
             camera: Position { x: 0, y: 0}
+        };
+        world.load_backdrops(ctx);
+
+        world
+    }
+
+    // location backdrops come from the universe sites and are reloaded whenever the universe is replaced
+    pub fn load_backdrops(&mut self, ctx: &mut Context) {
+        self.backdrops.retain(|id, _| match id {
+            BackdropId::Location(_) => false,
+            _ => true
+        });
+
+        for site in self.universe.sites.iter() {
+            if let Some(ref backdrop) = site.backdrop {
+                match Image::new(ctx, backdrop) {
+                    Ok(image) => {
+                        self.backdrops.insert(BackdropId::Location(site.location.clone()), image);
+                    },
+                    Err(error) => println!("backdrop {} not loaded: {}", backdrop, error)
+                }
+            }
         }
     }
 }
@@ -201,6 +790,7 @@ impl Level {
         self.npc.clear();
         self.pilot_seats.clear();
         self.decorations.clear();
+        self.spots.clear();
     }
 
     pub fn check_player_collision(&self, direction: &Direction) -> bool {
@@ -227,8 +817,12 @@ impl Level {
             found_collision = true;
         }
 
+        if let Some(_) = self.pilot_seats.get(collision_tile) {
+            found_collision = true;
+        }
+
         if let Some(door) = self.doors.get(collision_tile) {
-            if let DoorStatus::Closed = door.status {
+            if door.status != DoorStatus::Open {
                 found_collision = true;
             }
         }
@@ -244,6 +838,10 @@ impl Level {
         }
     }
 
+    pub fn powered_circuitry(&self) -> usize {
+        self.circuitry.iter().filter(|circuitry| circuitry.as_ref().map(|circuitry| circuitry.powered()).unwrap_or(false)).count()
+    }
+
     pub fn update_power(&mut self) {
         self.reset_powert();
         for (generator_index, generator) in self.generators.iter().enumerate() {
@@ -257,7 +855,7 @@ impl Level {
                     let mut root = generator_pos;
 
                     if let Some(circuitry) = self.circuitry.get(root) {
-                        if circuitry.contains(Item::PowerConductor) {
+                        if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                             open_set.push_back(root);
                         }
                     }
@@ -276,7 +874,7 @@ impl Level {
                             
                             if let None = open_set.iter().find(|&&visited| (neighbor == visited)) {
                                 if let Some(circuitry) = self.circuitry.get(neighbor) {
-                                    if circuitry.contains(Item::PowerConductor) {
+                                    if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                                         open_set.push_back(neighbor);
                                     }
                                 }
@@ -284,7 +882,7 @@ impl Level {
                         }
 
                         if let Some(circuitry) = self.circuitry.get(subtree_root_position) {
-                            if circuitry.contains(Item::PowerConductor) {
+                            if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                                 closed_set.insert(subtree_root);
                             }
                         }
@@ -311,7 +909,7 @@ impl Level {
                 let mut root = self.player.front_tile;
                 
                 if let Some(circuitry) = self.circuitry.get(root) {
-                        if circuitry.contains(Item::PowerConductor) {
+                        if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                             open_set.push_back(root);
                         }
                     }
@@ -330,7 +928,7 @@ impl Level {
                         
                         if let None = open_set.iter().find(|&&visited| (neighbor == visited)) {
                             if let Some(circuitry) = self.circuitry.get(neighbor) {
-                                if circuitry.contains(Item::PowerConductor) {
+                                if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                                     open_set.push_back(neighbor);
                                 }
                             }
@@ -338,7 +936,7 @@ impl Level {
                     }
 
                     if let Some(circuitry) = self.circuitry.get(subtree_root_position) {
-                        if circuitry.contains(Item::PowerConductor) {
+                        if circuitry.contains(Item::PowerConductor, CIRCUITRY_CONDUCTORS) {
                             closed_set.insert(subtree_root);
                             if let Some(pos) = subtree_root {
                                 if pos != self.player.front_tile {
@@ -359,6 +957,348 @@ impl Level {
         connected
     }
 
+    // exit doors of a ship lead to where the ship is docked (space when undocked),
+    // the landing pad of a planet leads back to the ship landed on it
+    pub fn update_exits(&mut self, universe: &Universe) {
+        let mut destination = None;
+        match self.location {
+            Location::Ship(ref ship_id) => {
+                if let Some(ship) = universe.ship(ship_id) {
+                    destination = Some(ship.docked.clone());
+                }
+            },
+            Location::Planet(_) => {
+                if let Some(ship) = universe.ships.iter().find(|ship| ship.docked == self.location) {
+                    destination = Some(Location::Ship(ship.id.clone()));
+                }
+            },
+            _ => { }
+        }
+
+        if let Some(destination) = destination {
+            for door in self.doors.iter_mut() {
+                if let &mut Some(ref mut door) = door {
+                    if let DoorType::Exit(_) = door.variant {
+                        door.variant = DoorType::Exit(destination.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    // crews of ships docked at a station wait next to its exit and leave again with their ship,
+    // a pilot the player is talking to finishes the conversation first
+    pub fn update_visitors(&mut self, universe: &mut Universe) {
+        let mut departed = vec![];
+        for (index, npc) in self.npc.iter().enumerate() {
+            if let &Some(Npc { ship: Some(ref ship_id), .. }) = npc {
+                let pos = Position::from_int(index as i32);
+                if pos != self.player.front_tile && universe.ship(ship_id).map_or(true, |ship| ship.docked != self.location) {
+                    departed.push(pos);
+                }
+            }
+        }
+        for pos in departed {
+            if let Some(&Npc { ship: Some(ref ship_id), ref memory, .. }) = self.npc.get(pos) {
+                if let Some(ship) = universe.ship_mut(ship_id) {
+                    ship.pilot_memory = memory.clone();
+                }
+            }
+            self.npc.remove(pos);
+        }
+
+        if let Location::Station(_) = self.location {
+            for ship in universe.ships.iter() {
+                if ship.docked != self.location || ship.orders == Orders::Idle {
+                    continue;
+                }
+
+                let aboard = self.npc.iter().any(|npc| match npc {
+                    &Some(Npc { ship: Some(ref ship_id), .. }) => ship_id == &ship.id,
+                    _ => false
+                });
+                if !aboard {
+                    if let Some(pos) = self.free_tile_near_exit() {
+                        let mut pilot = savegame::static_npc::pilot(Direction::Down, &ship.id, &ship.faction);
+                        pilot.routine = Routine::Wander(pos, VISITOR_WANDER_RADIUS);
+                        pilot.memory = ship.pilot_memory.clone();
+                        self.npc.insert(pos, pilot);
+                    }
+                }
+            }
+        }
+    }
+
+    // moves every npc one step along its routine, npcs stay put while the player faces them
+    pub fn update_npcs(&mut self, universe: &Universe) {
+        let mut rng = thread_rng();
+        let hour = universe.time.as_secs() % SECONDS_PER_DAY / 3600;
+
+        let mut positions = vec![];
+        for (index, npc) in self.npc.iter().enumerate() {
+            if npc.is_some() {
+                positions.push(Position::from_int(index as i32));
+            }
+        }
+
+        for pos in positions {
+            if pos == self.player.front_tile {
+                continue;
+            }
+
+            let mut npc = match self.npc.get(pos) {
+                Some(npc) => npc.clone(),
+                None => continue
+            };
+            // a guard who distrusts the player leaves its routine to keep an eye on them
+            if npc.variant == NpcType::Guard && universe.standing(&npc) <= DISTRUST_STANDING {
+                let offset = &self.player.position - &pos;
+                npc.direction = if offset.x.abs() > offset.y.abs() {
+                    if offset.x > 0 { Direction::Right } else { Direction::Left }
+                } else {
+                    if offset.y > 0 { Direction::Down } else { Direction::Up }
+                };
+                npc.look_at = npc.direction;
+                self.npc.insert(pos, npc);
+                continue;
+            }
+
+            let goal = match npc.routine.clone() {
+                Routine::Idle => None,
+                Routine::Patrol(ref waypoints) if !waypoints.is_empty() => {
+                    if waypoints[npc.waypoint % waypoints.len()] == pos {
+                        npc.waypoint = (npc.waypoint + 1) % waypoints.len();
+                    }
+                    Some(waypoints[npc.waypoint % waypoints.len()])
+                },
+                Routine::Patrol(_) => None,
+                Routine::Wander(origin, radius) => {
+                    let directions = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+                    let step = &pos + &rng.choose(&directions).unwrap().value();
+                    if rng.gen_bool(WANDER_CHANCE) && (step.x - origin.x).abs() <= radius && (step.y - origin.y).abs() <= radius {
+                        Some(step)
+                    } else {
+                        None
+                    }
+                },
+                Routine::Schedule(ref entries) => {
+                    // before the first entry of the day the last one of the day before still applies
+                    let entry = entries.iter().filter(|entry| entry.0 <= hour).last().or(entries.last());
+                    entry.and_then(|entry| self.spot(&entry.1))
+                },
+                Routine::Follow => self.next_to(pos, self.player.position),
+                Routine::Manning(_, station) => self.next_to(pos, station)
+            };
+
+            let mut next = None;
+            if let Some(goal) = goal {
+                if goal != pos {
+                    next = self.find_path(pos, goal).and_then(|path| path.first().cloned());
+                }
+            }
+
+            match next {
+                Some(next) => {
+                    for direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right].iter() {
+                        if &pos + &direction.value() == next {
+                            npc.direction = *direction;
+                            npc.look_at = *direction;
+                        }
+                    }
+                    self.npc.remove(pos);
+                    self.npc.insert(next, npc);
+                },
+                None => {
+                    self.npc.insert(pos, npc);
+                }
+            }
+        }
+    }
+
+    // every interval since the last restock brings back one of each item a trader runs short of
+    pub fn restock(&mut self, time: Duration) {
+        let now = time.as_secs();
+        for npc in self.npc.iter_mut() {
+            if let Some(npc) = npc {
+                let intervals = (now.saturating_sub(npc.stock.restocked) / RESTOCK_INTERVAL) as usize;
+                if intervals == 0 {
+                    continue;
+                }
+                npc.stock.restocked = now;
+                for &(item, target) in npc.stock.sells.iter() {
+                    let count = npc.inventory.count(&item);
+                    if count < target {
+                        npc.inventory.add(item, min(target, count + intervals) - count);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn spot(&self, name: &str) -> Option<Position> {
+        self.spots.iter().find(|spot| spot.0 == name).map(|spot| spot.1)
+    }
+
+    // breadth first search over the level grid, the path excludes the start
+    pub fn find_path(&self, from: Position, to: Position) -> Option<Vec<Position>> {
+        let mut open = VecDeque::new();
+        let mut came_from: BTreeMap<Position, Position> = BTreeMap::new();
+        open.push_back(from);
+        came_from.insert(from, from);
+
+        while let Some(current) = open.pop_front() {
+            if current == to {
+                let mut path = vec![];
+                let mut step = current;
+                while step != from {
+                    path.push(step);
+                    step = came_from[&step];
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+
+            for direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right].iter() {
+                let neighbor = &current + &direction.value();
+                if !came_from.contains_key(&neighbor) && self.is_walkable(neighbor) {
+                    came_from.insert(neighbor, current);
+                    open.push_back(neighbor);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn is_walkable(&self, pos: Position) -> bool {
+        let open_door = match self.doors.get(pos) {
+            Some(door) => door.status == DoorStatus::Open,
+            None => true
+        };
+
+        pos.x >= 0 && pos.y >= 0 && pos.x < LEVEL_SIZE && pos.y < LEVEL_SIZE
+            && open_door
+            && self.floor.get(pos).is_some()
+            && self.walls.get(pos).is_none()
+            && self.terminals.get(pos).is_none()
+            && self.generators.get(pos).is_none()
+            && self.storages.get(pos).is_none()
+            && self.pilot_seats.get(pos).is_none()
+            && self.npc.get(pos).is_none()
+            && self.player.position != pos
+    }
+
+    // the walkable tile next to the target that is the fewest steps away, npcs already next to it stay
+    fn next_to(&self, from: Position, target: Position) -> Option<Position> {
+        if (from.x - target.x).abs() + (from.y - target.y).abs() == 1 {
+            return Some(from);
+        }
+
+        [Direction::Up, Direction::Down, Direction::Left, Direction::Right].iter()
+            .map(|direction| &target + &direction.value())
+            .filter(|&neighbor| self.is_walkable(neighbor))
+            .filter_map(|neighbor| self.find_path(from, neighbor).map(|path| (path.len(), neighbor)))
+            .min()
+            .map(|(_, neighbor)| neighbor)
+    }
+
+    fn free_tile_near_exit(&self) -> Option<Position> {
+        let mut exits = vec![];
+        for (index, door) in self.doors.iter().enumerate() {
+            if let &Some(Door { variant: DoorType::Exit(_), .. }) = door {
+                exits.push(Position::from_int(index as i32));
+            }
+        }
+
+        self.free_tile_near(&exits)
+    }
+
+    fn free_tile_near(&self, targets: &[Position]) -> Option<Position> {
+        let mut free = vec![];
+        for index in 0..LEVEL_SIZE * LEVEL_SIZE {
+            let pos = Position::from_int(index);
+            if self.is_free(pos) {
+                if let Some(distance) = targets.iter().map(|target| (target.dist(&pos) * 10.0) as i32).min() {
+                    free.push((distance, pos));
+                }
+            }
+        }
+        free.sort();
+
+        free.first().map(|&(_, pos)| pos)
+    }
+
+    // crew members following the player leave the level together with them
+    pub fn take_followers(&mut self) -> Vec<(Position, Npc)> {
+        let mut followers = vec![];
+        for index in 0..LEVEL_SIZE * LEVEL_SIZE {
+            let pos = Position::from_int(index);
+            if let Some(npc) = self.npc.get(pos).cloned() {
+                if npc.routine == Routine::Follow {
+                    self.npc.remove(pos);
+                    followers.push((pos, npc));
+                }
+            }
+        }
+
+        followers
+    }
+
+    // returns the followers without a free tile next to the player, they stay where they came from
+    pub fn place_followers(&mut self, followers: Vec<(Position, Npc)>) -> Vec<(Position, Npc)> {
+        let mut stranded = vec![];
+        for (origin, follower) in followers {
+            let player = self.player.position;
+            match self.free_tile_near(&[player]) {
+                Some(pos) => self.npc.insert(pos, follower),
+                None => stranded.push((origin, follower))
+            }
+        }
+
+        stranded
+    }
+
+    pub fn station_position(&self, station: CrewStation) -> Option<Position> {
+        for index in 0..LEVEL_SIZE * LEVEL_SIZE {
+            let pos = Position::from_int(index);
+            let found = match station {
+                CrewStation::Generator => self.generators.get(pos).is_some(),
+                CrewStation::Console => self.terminals.get(pos).map_or(false, |terminal| terminal.variant == TerminalType::ShipConsole)
+            };
+            if found {
+                return Some(pos);
+            }
+        }
+
+        None
+    }
+
+    pub fn manned_stations(&self) -> Vec<CrewStation> {
+        let mut stations = vec![];
+        for npc in self.npc.iter() {
+            if let &Some(Npc { routine: Routine::Manning(station, _), .. }) = npc {
+                if !stations.contains(&station) {
+                    stations.push(station);
+                }
+            }
+        }
+
+        stations
+    }
+
+    fn is_free(&self, pos: Position) -> bool {
+        self.floor.get(pos).is_some()
+            && self.walls.get(pos).is_none()
+            && self.doors.get(pos).is_none()
+            && self.terminals.get(pos).is_none()
+            && self.generators.get(pos).is_none()
+            && self.storages.get(pos).is_none()
+            && self.pilot_seats.get(pos).is_none()
+            && self.npc.get(pos).is_none()
+            && self.player.position != pos
+    }
+
     pub fn current_storage(&mut self) -> Option<&mut Storage> {
         if let Some(current_storage) = self.storages.get_mut(self.player.front_tile) {
             Some(current_storage)
@@ -383,6 +1323,50 @@ impl Level {
         }
     }
 
+    pub fn current_pilot_seat(&mut self) -> Option<&mut PilotSeat> {
+        self.pilot_seats.get_mut(self.player.front_tile)
+    }
+
+    pub fn is_seated(&self) -> bool {
+        self.pilot_seats.get(self.player.position).is_some()
+    }
+
+    // the player sits down from the front of the seat and faces the way it points away from
+    pub fn sit_down(&mut self) {
+        let seat_position = self.player.front_tile;
+        if let Some(seat) = self.pilot_seats.get(seat_position) {
+            self.player.direction = match seat.front {
+                Direction::Up => Direction::Down,
+                Direction::Down => Direction::Up,
+                Direction::Left => Direction::Right,
+                Direction::Right => Direction::Left
+            };
+            self.player.position = seat_position;
+            self.player.movement.clear();
+            self.player.front_tile = &self.player.direction.value() + &self.player.position;
+        }
+    }
+
+    // the player steps out to the front of the seat, or to any other free side when someone stands there,
+    // returns false when the seat is boxed in
+    pub fn stand_up(&mut self) -> bool {
+        let seat_position = self.player.position;
+        let front = match self.pilot_seats.get(seat_position) {
+            Some(seat) => seat.front,
+            None => return true
+        };
+        let sides = [front, Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+        match sides.iter().find(|side| self.is_walkable(&side.value() + &seat_position)) {
+            Some(&side) => {
+                self.player.direction = side;
+                self.player.position = &side.value() + &seat_position;
+                self.player.front_tile = &self.player.direction.value() + &self.player.position;
+                true
+            },
+            None => false
+        }
+    }
+
     pub fn current_npc(&mut self) -> Option<&mut Npc> {
         if let Some(current_npc) = self.npc.get_mut(self.player.front_tile) {
             Some(current_npc)